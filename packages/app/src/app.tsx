@@ -107,7 +107,7 @@ export function AppInterface(props: { defaultUrl?: string; children?: JSX.Elemen
     if (import.meta.env.DEV)
       return `http://${import.meta.env.VITE_OPENCODE_SERVER_HOST ?? "localhost"}:${import.meta.env.VITE_OPENCODE_SERVER_PORT ?? "4096"}`
 
-    // In production Tauri builds, the sidecar runs on localhost:4096
+    // Tauri builds pass the sidecar's actual port as defaultUrl; this is only a fallback.
     // window.location.origin would be tauri://localhost which is wrong
     return "http://localhost:4096"
   }
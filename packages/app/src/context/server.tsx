@@ -112,6 +112,15 @@ export const { use: useServer, provider: ServerProvider } = createSimpleContext(
       setState("active", url)
     })
 
+    // The default can move, e.g. when the desktop sidecar restarts on another port; follow
+    // it unless another server was picked.
+    let lastDefault = defaultUrl
+    createEffect(() => {
+      const url = normalizeServerUrl(props.defaultUrl)
+      if (url && url !== lastDefault && state.active === lastDefault) setState("active", url)
+      lastDefault = url
+    })
+
     const isReady = createMemo(() => {
       const r = ready()
       const a = !!state.active
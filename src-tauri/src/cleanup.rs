@@ -0,0 +1,99 @@
+use crate::updater::{UPDATE_LOCK_FILE, UPDATE_TEMP_PREFIX};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Update temp directories untouched for longer than this are swept at startup.
+/// The same age marks an update lockfile as abandoned (the updater crashed).
+pub const STALE_UPDATE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct CleanupReport {
+    pub removed: Vec<String>,
+    pub skipped_locked: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+/// Removes Godoty-owned update directories (`godoty-update*`) from the system temp dir.
+/// With `max_age` set, only directories older than it are removed (startup sweep);
+/// with `None`, every unlocked directory is removed (manual cleanup).
+pub fn sweep_update_temp(max_age: Option<Duration>) -> CleanupReport {
+    let mut report = CleanupReport::default();
+    let temp_dir = std::env::temp_dir();
+
+    let entries = match fs::read_dir(&temp_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("[Cleanup] Failed to read temp dir {:?}: {}", temp_dir, e);
+            return report;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(UPDATE_TEMP_PREFIX) {
+            continue;
+        }
+
+        // Never follow symlinks out of the temp dir.
+        let path = entry.path();
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(m) if m.is_dir() => m,
+            _ => continue,
+        };
+
+        if is_locked(&path) {
+            println!("[Cleanup] Skipping {:?}: update in progress", path);
+            report
+                .skipped_locked
+                .push(path.to_string_lossy().to_string());
+            continue;
+        }
+
+        if let Some(max_age) = max_age {
+            if age_of(&metadata).is_none_or(|age| age < max_age) {
+                continue;
+            }
+        }
+
+        let size = dir_size(&path);
+        match fs::remove_dir_all(&path) {
+            Ok(()) => {
+                println!("[Cleanup] Removed {:?} ({} bytes)", path, size);
+                report.bytes_freed += size;
+                report.removed.push(path.to_string_lossy().to_string());
+            }
+            Err(e) => eprintln!("[Cleanup] Failed to remove {:?}: {}", path, e),
+        }
+    }
+
+    report
+}
+
+/// A directory is locked while it holds an update lockfile that isn't itself stale.
+fn is_locked(dir: &Path) -> bool {
+    match fs::metadata(dir.join(UPDATE_LOCK_FILE)) {
+        Ok(lock) => age_of(&lock).is_none_or(|age| age < STALE_UPDATE_AGE),
+        Err(_) => false,
+    }
+}
+
+fn age_of(metadata: &fs::Metadata) -> Option<Duration> {
+    let modified = metadata.modified().ok()?;
+    SystemTime::now().duration_since(modified).ok()
+}
+
+/// Total size of the files under `path`, without following symlinks.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match fs::symlink_metadata(entry.path()) {
+            Ok(m) if m.is_dir() => dir_size(&entry.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
@@ -1,6 +1,28 @@
-use crate::sidecar::SidecarManager;
-use crate::updater::{Updater, Release};
-use tauri::{AppHandle, Runtime};
+use crate::cleanup::{self, CleanupReport};
+use crate::config::{get_config_dir, get_logs_dir, ACTIVE_LOG_FILE};
+use crate::diagnostics::{self, Diagnostics};
+use crate::docs::{self, DocsSource, DocsStatus};
+use crate::drift::{self, DriftReport};
+use crate::godot_path::{self, GodotPath};
+use crate::orphans::{self, OrphanedSidecar};
+use crate::retention::{self, RetentionReport};
+use crate::settings::{self, LogRetention, Settings};
+use crate::setup;
+use crate::sidecar::{HealthStatus, OutputLine, ReloadOutcome, SidecarManager};
+use crate::updater::{
+    self, ConnectivityReport, ExpectedAsset, PermissionFix, Release, UpdateComplete, UpdateGuard,
+    Updater, UpdaterError, VersionCompatibility,
+};
+use crate::version::{self, VersionStatus};
+use crate::watcher;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_opener::OpenerExt;
+
+/// Config subdirectories the UI may open directly.
+const OPENABLE_SUBDIRS: &[&str] = &["logs", "godot_docs", "mcp-servers", "data"];
 
 #[derive(serde::Serialize)]
 pub struct SidecarVersion {
@@ -13,14 +35,17 @@ pub struct UpdateInfo {
     pub available: bool,
     pub latest_version: String,
     pub current_version: String,
+    pub version_status: VersionStatus,
     pub release: Option<Release>,
 }
 
 #[tauri::command]
-pub fn get_sidecar_version<R: Runtime>(app: AppHandle<R>) -> Result<SidecarVersion, String> {
+pub fn get_sidecar_version<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<SidecarVersion, UpdaterError> {
     let updater = Updater::new(&app);
-    let path = updater.get_sidecar_path().map_err(|e| e.to_string())?;
-    let version = updater.get_current_version().map_err(|e| e.to_string())?;
+    let path = updater.get_sidecar_path()?;
+    let version = updater.get_current_version()?;
     Ok(SidecarVersion {
         version,
         path: path.to_string_lossy().to_string(),
@@ -28,57 +53,397 @@ pub fn get_sidecar_version<R: Runtime>(app: AppHandle<R>) -> Result<SidecarVersi
 }
 
 #[tauri::command]
-pub async fn check_sidecar_update<R: Runtime>(app: AppHandle<R>) -> Result<UpdateInfo, String> {
+pub async fn check_sidecar_update<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<UpdateInfo, UpdaterError> {
     // Run blocking network call in a blocking thread to avoid blocking the async runtime
     let app_handle = app.clone();
     let result = tauri::async_runtime::spawn_blocking(move || {
         let updater = Updater::new(&app_handle);
-        let current_version = updater.get_current_version().map_err(|e| e.to_string())?;
-        let release = updater.get_latest_release().map_err(|e| e.to_string())?;
-        Ok::<(String, Release), String>((current_version, release))
-    }).await.map_err(|e| e.to_string())??;
+        let current_version = updater.get_current_version()?;
+        let release = updater.get_latest_release()?;
+        Ok::<(String, Release), UpdaterError>((current_version, release))
+    })
+    .await??;
 
     let (current_version, release) = result;
     
-    let latest_ver_str = release.tag_name.trim_start_matches('v');
-    let current_ver_str = current_version.trim_start_matches('v');
-    
-    let available = if let (Ok(latest), Ok(current)) = (
-        semver::Version::parse(latest_ver_str),
-        semver::Version::parse(current_ver_str)
-    ) {
-        latest > current
-    } else {
-        latest_ver_str != current_ver_str && latest_ver_str != "0.0.0"
+    let (available, version_status) = match version::compare(&release.tag_name, &current_version) {
+        Some(ordering) => (ordering == Ordering::Greater, VersionStatus::Comparable),
+        None => {
+            eprintln!(
+                "[Updater] Cannot compare release '{}' with installed '{}'",
+                release.tag_name, current_version
+            );
+            (false, VersionStatus::Unparseable)
+        }
     };
-    
+
     Ok(UpdateInfo {
         available,
         latest_version: release.tag_name.clone(),
         current_version,
+        version_status,
         release: Some(release),
     })
 }
 
+/// Health check polls allowed for a freshly installed sidecar before it is rolled back.
+const UPDATE_HEALTH_ATTEMPTS: u32 = 10;
+
 #[tauri::command]
-pub async fn perform_sidecar_update<R: Runtime>(app: AppHandle<R>, release: Release) -> Result<(), String> {
-    let app_handle = app.clone();
-    let release_clone = release.clone();
-    
-    // Download and install update in background thread
+pub async fn perform_sidecar_update<R: Runtime>(
+    app: AppHandle<R>,
+    release: Release,
+) -> Result<(), UpdaterError> {
+    let in_progress = app.state::<UpdateGuard>().try_begin()?;
+    // Download, install and verify the update in a background thread
     tauri::async_runtime::spawn_blocking(move || {
-        let updater = Updater::new(&app_handle);
-        updater.perform_update(&release_clone).map_err(|e| e.to_string())
-    }).await
-      .map_err(|e| e.to_string())??;
-    
-    // Restart sidecar on main thread (or safe context)
-    SidecarManager::restart_sidecar(&app);
-    Ok(())
+        let _in_progress = in_progress;
+        let updater = Updater::new(&app);
+        let version = match updater.perform_update(&release) {
+            Ok(version) => version,
+            Err(error) => {
+                // The sidecar may already have been stopped for the install; bring back
+                // whatever binary the rollback or the bundle fallback left in place.
+                let stopped = SidecarManager::probe_health(&app) != HealthStatus::Healthy;
+                if stopped && updater.get_sidecar_path().is_ok_and(|p| p.exists()) {
+                    SidecarManager::restart_sidecar(&app);
+                }
+                let _ = app.emit("sidecar-update-error", updater::UpdateFailed::from(&error));
+                return Err(error);
+            }
+        };
+
+        // The new binary must run at all before it replaces the live sidecar.
+        let verified = updater.smoke_test().and_then(|_| {
+            SidecarManager::restart_sidecar(&app);
+            if SidecarManager::wait_until_healthy(&app, UPDATE_HEALTH_ATTEMPTS) {
+                Ok(())
+            } else {
+                Err(UpdaterError::Verification(
+                    "sidecar did not pass its health check".to_string(),
+                ))
+            }
+        });
+        let error = match verified {
+            Ok(()) => {
+                if let Err(e) = updater.discard_backup() {
+                    eprintln!("[Updater] Failed to remove backup: {}", e);
+                }
+                let _ = app.emit("sidecar-update-complete", UpdateComplete { version });
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+
+        eprintln!("[Updater] New sidecar failed verification: {}", error);
+        let context = match updater.restore_backup() {
+            Ok(true) => {
+                SidecarManager::restart_sidecar(&app);
+                "rolled back to the previous sidecar".to_string()
+            }
+            Ok(false) => "no previous sidecar to roll back to".to_string(),
+            Err(e) => format!("rolling back also failed: {}", e),
+        };
+        let error = UpdaterError::WithContext {
+            error: Box::new(error),
+            context,
+        };
+        let _ = app.emit("sidecar-update-error", updater::UpdateFailed::from(&error));
+        Err(error)
+    })
+    .await?
+}
+
+/// Stops the sidecar, replaces its binary with the bundled one and starts it again.
+/// Returns the reinstalled version.
+#[tauri::command]
+pub async fn reinstall_sidecar<R: Runtime>(app: AppHandle<R>) -> Result<String, UpdaterError> {
+    let in_progress = app.state::<UpdateGuard>().try_begin()?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let _in_progress = in_progress;
+        SidecarManager::shutdown(&app);
+        let result = Updater::new(&app).force_reinstall();
+        // Also after a failure: the old binary is untouched when no bundle was found.
+        SidecarManager::restart_sidecar(&app);
+        result
+    })
+    .await?
 }
 
 #[tauri::command]
 pub async fn restart_sidecar<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
-    SidecarManager::restart_sidecar(&app);
-    Ok(())
+    tauri::async_runtime::spawn_blocking(move || SidecarManager::restart_sidecar(&app))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn cleanup_temp() -> Result<CleanupReport, String> {
+    tauri::async_runtime::spawn_blocking(|| cleanup::sweep_update_temp(None))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_settings<R: Runtime>(app: AppHandle<R>) -> Settings {
+    settings::current(&app)
+}
+
+#[tauri::command]
+pub async fn set_log_retention<R: Runtime>(
+    app: AppHandle<R>,
+    retention: LogRetention,
+) -> Result<RetentionReport, String> {
+    settings::update(&app, |s| s.log_retention = retention).map_err(|e| e.to_string())?;
+    enforce_retention(app).await
+}
+
+#[tauri::command]
+pub async fn enforce_retention<R: Runtime>(app: AppHandle<R>) -> Result<RetentionReport, String> {
+    let logs_dir = get_logs_dir(&app).map_err(|e| e.to_string())?;
+    let policy = settings::current(&app).log_retention;
+    tauri::async_runtime::spawn_blocking(move || retention::enforce(&logs_dir, &policy))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn check_update_connectivity<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<ConnectivityReport, String> {
+    tauri::async_runtime::spawn_blocking(move || Updater::new(&app).check_connectivity())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_docs_status<R: Runtime>(app: AppHandle<R>) -> Result<DocsStatus, String> {
+    let config_dir = get_config_dir(&app).map_err(|e| e.to_string())?;
+    let classes_dir = config_dir.join("godot_docs/classes");
+    let configured = setup::effective_docs_source(&app);
+    Ok(docs::status(&classes_dir, configured))
+}
+
+#[tauri::command]
+pub async fn set_docs_source<R: Runtime>(
+    app: AppHandle<R>,
+    source: DocsSource,
+) -> Result<DocsStatus, String> {
+    if docs::env_docs_source().is_some() {
+        return Err(
+            "GODOTY_DOCS_DIR is set and overrides the docs source; unset it to choose one here"
+                .to_string(),
+        );
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        // Persisted first, so the docs on disk never belong to a source that isn't saved.
+        let previous = settings::current(&app).docs_source;
+        let configured = source.clone();
+        settings::update(&app, |s| s.docs_source = source.clone()).map_err(|e| e.to_string())?;
+        if let Err(e) = setup::apply_docs_source(&app, &source) {
+            // The previous docs are still in place, so the previous source is restored too.
+            if let Err(revert) = settings::update(&app, |s| s.docs_source = previous) {
+                eprintln!(
+                    "[Setup] Failed to restore the previous docs source: {}",
+                    revert
+                );
+            }
+            return Err(e.to_string());
+        }
+
+        // The doc server indexes the classes dir at startup.
+        SidecarManager::restart_sidecar(&app);
+
+        let config_dir = get_config_dir(&app).map_err(|e| e.to_string())?;
+        let classes_dir = config_dir.join("godot_docs/classes");
+        Ok(docs::status(&classes_dir, configured))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Points the docs at a custom directory, or back at the bundled set with `None`.
+#[tauri::command]
+pub async fn set_docs_dir<R: Runtime>(
+    app: AppHandle<R>,
+    path: Option<String>,
+) -> Result<DocsStatus, String> {
+    let source = match path {
+        Some(path) => DocsSource::Custom { path: path.into() },
+        None => DocsSource::Bundled,
+    };
+    set_docs_source(app, source).await
+}
+
+#[tauri::command]
+pub async fn get_version_compatibility<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<VersionCompatibility, UpdaterError> {
+    tauri::async_runtime::spawn_blocking(move || Updater::new(&app).get_compatibility()).await?
+}
+
+#[tauri::command]
+pub async fn reload_sidecar_config<R: Runtime>(app: AppHandle<R>) -> Result<ReloadOutcome, String> {
+    tauri::async_runtime::spawn_blocking(move || SidecarManager::reload_config(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn stop_detached_sidecar<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(move || SidecarManager::stop_detached(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn fix_sidecar_permissions<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<PermissionFix, UpdaterError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Updater::new(&app).get_sidecar_path()?;
+        Ok(updater::fix_permissions(&path)?)
+    })
+    .await?
+}
+
+#[tauri::command]
+pub fn get_sidecar_output_buffer<R: Runtime>(app: AppHandle<R>) -> Vec<OutputLine> {
+    SidecarManager::startup_output(&app)
+}
+
+#[tauri::command]
+pub fn get_expected_asset<R: Runtime>(app: AppHandle<R>) -> ExpectedAsset {
+    Updater::new(&app).expected_asset()
+}
+
+#[tauri::command]
+pub fn is_first_run<R: Runtime>(app: AppHandle<R>) -> bool {
+    app.try_state::<setup::FirstRun>()
+        .is_some_and(|state| state.0)
+}
+
+/// Changes the sidecar's listen address and restarts it. A non-loopback host is refused
+/// unless `allow_network_access` confirms the user accepts exposing it to the network.
+#[tauri::command]
+pub async fn set_sidecar_bind_host<R: Runtime>(
+    app: AppHandle<R>,
+    host: String,
+    allow_network_access: bool,
+) -> Result<Settings, String> {
+    let ip: std::net::IpAddr = host
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid IP address", host))?;
+    if !ip.is_loopback() && !allow_network_access {
+        return Err(format!(
+            "Binding to {} exposes the sidecar to your network; confirm with allow_network_access",
+            ip
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let settings = settings::update(&app, |s| {
+            s.sidecar_bind_host = ip.to_string();
+            s.allow_network_access = allow_network_access;
+        })
+        .map_err(|e| e.to_string())?;
+        SidecarManager::restart_sidecar(&app);
+        Ok(settings)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn detect_drift<R: Runtime>(app: AppHandle<R>) -> Result<DriftReport, String> {
+    tauri::async_runtime::spawn_blocking(move || drift::detect(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn reload_settings<R: Runtime>(app: AppHandle<R>) -> Settings {
+    settings::reload(&app)
+}
+
+#[tauri::command]
+pub fn set_watch_config<R: Runtime>(app: AppHandle<R>, enabled: bool) -> Result<Settings, String> {
+    watcher::set_enabled(&app, enabled)?;
+    settings::update(&app, |s| s.watch_config = enabled).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn list_orphaned_sidecars<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<Vec<OrphanedSidecar>, String> {
+    tauri::async_runtime::spawn_blocking(move || orphans::list(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn kill_orphaned_sidecars<R: Runtime>(app: AppHandle<R>) -> Result<Vec<u32>, String> {
+    tauri::async_runtime::spawn_blocking(move || orphans::kill(&app))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_sidecar_port<R: Runtime>(app: AppHandle<R>) -> u16 {
+    SidecarManager::active_port(&app)
+}
+
+#[tauri::command]
+pub fn get_sidecar_log_path<R: Runtime>(app: AppHandle<R>) -> Result<String, String> {
+    let path = get_logs_dir(&app)
+        .map_err(|e| e.to_string())?
+        .join(ACTIVE_LOG_FILE);
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub fn open_config_dir<R: Runtime>(app: AppHandle<R>) -> Result<String, String> {
+    let dir = get_config_dir(&app).map_err(|e| e.to_string())?;
+    open_dir(&app, &dir)
+}
+
+/// Opens one of `OPENABLE_SUBDIRS`; any other name is rejected rather than joined, so the
+/// UI cannot reach outside the config directory.
+#[tauri::command]
+pub fn open_config_subdir<R: Runtime>(app: AppHandle<R>, name: String) -> Result<String, String> {
+    if !OPENABLE_SUBDIRS.contains(&name.as_str()) {
+        return Err(format!(
+            "Unknown config subdirectory '{}', expected one of: {}",
+            name,
+            OPENABLE_SUBDIRS.join(", ")
+        ));
+    }
+    let dir = get_config_dir(&app).map_err(|e| e.to_string())?.join(&name);
+    open_dir(&app, &dir)
+}
+
+fn open_dir<R: Runtime>(app: &AppHandle<R>, dir: &Path) -> Result<String, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+    let path = dir.to_string_lossy().to_string();
+    app.opener()
+        .open_path(&path, None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub fn get_godot_path<R: Runtime>(app: AppHandle<R>) -> GodotPath {
+    godot_path::resolve(&app)
+}
+
+/// Update and health details for the troubleshoot panel. Each probe reports its own
+/// failure, so a missing sidecar still yields a report.
+#[tauri::command]
+pub async fn get_diagnostics<R: Runtime>(app: AppHandle<R>) -> Result<Diagnostics, String> {
+    tauri::async_runtime::spawn_blocking(move || diagnostics::collect(&app))
+        .await
+        .map_err(|e| e.to_string())
 }
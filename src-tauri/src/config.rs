@@ -1,11 +1,18 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tauri::{path::BaseDirectory, AppHandle, Manager, Runtime};
 
+/// Log file the running sidecar writes to, inside the logs directory.
+pub const ACTIVE_LOG_FILE: &str = "sidecar.log";
+/// Crash reports, inside the logs directory.
+pub const CRASH_REPORTS_DIR: &str = "crash-reports";
+/// Diagnostic snapshots, inside the logs directory.
+pub const SNAPSHOTS_DIR: &str = "snapshots";
+
 /// Returns the path to the Godoty configuration directory.
 /// Checks for a "data" directory next to the executable first (Portable Mode).
 /// Fallback: ~/.config/godoty/
 pub fn get_config_dir<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, tauri::Error> {
-    if let Ok(path) = std::env::var("GODOTY_CONFIG_DIR") {
+    if let Some(path) = std::env::var_os("GODOTY_CONFIG_DIR") {
         return Ok(PathBuf::from(path));
     }
 
@@ -20,6 +27,22 @@ pub fn get_config_dir<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf,
     app_handle.path().resolve("godoty", BaseDirectory::Config)
 }
 
+/// The config dir is templated into `opencode.json`, which can only hold UTF-8. Rather
+/// than let a lossy conversion hand the sidecar a path that doesn't exist, report it.
+pub fn require_utf8_path(path: &Path) -> Result<&str, String> {
+    path.to_str().ok_or_else(|| {
+        format!(
+            "Config directory path {:?} contains characters that are not valid UTF-8. \
+             Move it or set GODOTY_CONFIG_DIR to a path without them.",
+            path
+        )
+    })
+}
+
+pub fn get_logs_dir<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, tauri::Error> {
+    Ok(get_config_dir(app_handle)?.join("logs"))
+}
+
 pub fn get_sidecar_path<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, tauri::Error> {
     let config_dir = get_config_dir(app_handle)?;
     let bin_dir = config_dir.join("bin");
@@ -0,0 +1,88 @@
+use crate::config::get_config_dir;
+use crate::sidecar::{HealthStatus, SidecarManager};
+use crate::updater::Updater;
+use std::fmt::Display;
+use tauri::{AppHandle, Runtime};
+
+/// Outcome of one diagnostic probe; a failure is reported in place instead of failing
+/// the whole report.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Probe<T> {
+    pub value: Option<T>,
+    pub error: Option<String>,
+}
+
+impl<T, E: Display> From<Result<T, E>> for Probe<T> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Self {
+                value: Some(value),
+                error: None,
+            },
+            Err(e) => Self {
+                value: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Everything a bug report needs about the app and its sidecar, for the troubleshoot panel.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// Release target the updater looks for, e.g. `x86_64-unknown-linux-gnu`.
+    pub target: String,
+    pub config_dir: Probe<String>,
+    pub sidecar_path: Probe<String>,
+    pub sidecar_exists: bool,
+    /// `0.0.0` when the binary is missing.
+    pub sidecar_version: Probe<String>,
+    pub sidecar_port: u16,
+    pub sidecar_health: HealthStatus,
+}
+
+/// Gathers the report; runs the sidecar binary and probes its port, so call it off the
+/// async runtime.
+pub fn collect<R: Runtime>(app: &AppHandle<R>) -> Diagnostics {
+    let updater = Updater::new(app);
+    let sidecar_path = updater.get_sidecar_path();
+    let sidecar_exists = sidecar_path.as_ref().is_ok_and(|p| p.exists());
+
+    Diagnostics {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        target: updater.get_target_asset_name(),
+        config_dir: get_config_dir(app)
+            .map(|p| p.to_string_lossy().to_string())
+            .into(),
+        sidecar_path: sidecar_path.map(|p| p.to_string_lossy().to_string()).into(),
+        sidecar_exists,
+        sidecar_version: updater.get_current_version().into(),
+        sidecar_port: SidecarManager::active_port(app),
+        sidecar_health: SidecarManager::probe_health(app),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_probes_serialize_their_error() {
+        let ok: Probe<u16> = Ok::<_, String>(4096).into();
+        let failed: Probe<u16> = Err("binary not found").into();
+
+        assert_eq!(
+            serde_json::to_value(ok).unwrap(),
+            serde_json::json!({ "value": 4096, "error": null })
+        );
+        assert_eq!(
+            serde_json::to_value(failed).unwrap(),
+            serde_json::json!({ "value": null, "error": "binary not found" })
+        );
+    }
+}
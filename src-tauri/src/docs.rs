@@ -0,0 +1,340 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Marker in the classes directory recording where the current doc set came from.
+pub const DOCS_SOURCE_MARKER: &str = ".source";
+/// Marker in the classes directory recording the app version that copied the docs.
+pub const DOCS_VERSION_MARKER: &str = ".version";
+/// Downloaded doc sets live in `godot_docs/downloads/<version>`.
+pub const DOWNLOADED_DOCS_DIR: &str = "downloads";
+/// Class every complete doc set has; a directory without it is not a Godot docs dir.
+const REQUIRED_CLASS: &str = "@GlobalScope.xml";
+
+/// Where `godot_docs/classes` is populated from, persisted in `godoty.toml`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DocsSource {
+    /// The set shipped with the app, refreshed on every app upgrade.
+    #[default]
+    Bundled,
+    Custom {
+        path: PathBuf,
+    },
+    Downloaded {
+        version: String,
+    },
+}
+
+impl DocsSource {
+    /// Value written to the source marker.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DocsSource::Bundled => "bundled",
+            DocsSource::Custom { .. } => "custom",
+            DocsSource::Downloaded { .. } => "downloaded",
+        }
+    }
+}
+
+/// `GODOTY_DOCS_DIR`, which overrides the configured source with a custom directory.
+pub fn env_docs_source() -> Option<DocsSource> {
+    std::env::var_os("GODOTY_DOCS_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| DocsSource::Custom { path: dir.into() })
+}
+
+/// Checks that a user-supplied directory holds Godot class docs.
+pub fn validate_docs_dir(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("Godot docs directory not found: {:?}", dir));
+    }
+    if !dir.join(REQUIRED_CLASS).is_file() {
+        return Err(format!(
+            "{:?} does not look like a Godot docs directory: {} is missing",
+            dir, REQUIRED_CLASS
+        ));
+    }
+    Ok(())
+}
+
+/// Core classes compiled into the binary, used when no doc set can be found at all.
+const FALLBACK_DOCS: &[(&str, &str)] = &[
+    (
+        "@GlobalScope.xml",
+        include_str!("../resources/fallback_docs/@GlobalScope.xml"),
+    ),
+    (
+        "Object.xml",
+        include_str!("../resources/fallback_docs/Object.xml"),
+    ),
+    (
+        "Node.xml",
+        include_str!("../resources/fallback_docs/Node.xml"),
+    ),
+];
+
+#[derive(Debug, serde::Serialize)]
+pub struct DocsStatus {
+    /// Source of the docs on disk (`bundled`, `custom`, `downloaded` or `fallback`);
+    /// `unknown` when no marker has been written yet.
+    pub source: String,
+    /// Source selected in settings; differs from `source` after a fallback.
+    pub configured: DocsSource,
+    /// True when only the embedded core classes are available.
+    pub fallback: bool,
+    pub class_count: usize,
+    pub version: Option<String>,
+}
+
+/// Writes the embedded core classes into `classes_dir` and marks the set as `fallback`.
+pub fn write_fallback(classes_dir: &Path) -> std::io::Result<usize> {
+    fs::create_dir_all(classes_dir)?;
+    for (name, content) in FALLBACK_DOCS {
+        fs::write(classes_dir.join(name), content)?;
+    }
+    mark_source(classes_dir, "fallback")?;
+    Ok(FALLBACK_DOCS.len())
+}
+
+/// Files written by `sync_classes`.
+#[derive(Debug, Default, PartialEq, serde::Serialize)]
+pub struct SyncReport {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+/// Sorted `*.xml` class files in `dir`.
+pub fn list_classes(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "xml"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Copies every `*.xml` class file from `source_dir` into `target_dir` using up to
+/// `concurrency` threads. `on_progress(copied, total)` is called after each file.
+/// The first failure stops the remaining work; if several workers fail, the error for the
+/// file that sorts first is returned so the result does not depend on scheduling.
+pub fn copy_classes(
+    source_dir: &Path,
+    target_dir: &Path,
+    concurrency: usize,
+    on_progress: impl Fn(usize, usize) + Sync,
+) -> std::io::Result<usize> {
+    copy_files(
+        &list_classes(source_dir)?,
+        target_dir,
+        concurrency,
+        on_progress,
+    )
+}
+
+/// Copies only the class files that are missing from `target_dir` or differ from the
+/// source: a different size, or a source modified after the copy was made.
+pub fn sync_classes(
+    source_dir: &Path,
+    target_dir: &Path,
+    concurrency: usize,
+    on_progress: impl Fn(usize, usize) + Sync,
+) -> std::io::Result<SyncReport> {
+    let mut report = SyncReport::default();
+    let mut pending = Vec::new();
+    for path in list_classes(source_dir)? {
+        let target = target_dir.join(path.file_name().unwrap_or_default());
+        let Ok(target_meta) = fs::metadata(&target) else {
+            report.added += 1;
+            pending.push(path);
+            continue;
+        };
+        let source_meta = fs::metadata(&path)?;
+        let newer = match (source_meta.modified(), target_meta.modified()) {
+            (Ok(source), Ok(target)) => source > target,
+            _ => false,
+        };
+        if source_meta.len() != target_meta.len() || newer {
+            report.updated += 1;
+            pending.push(path);
+        } else {
+            report.skipped += 1;
+        }
+    }
+
+    copy_files(&pending, target_dir, concurrency, on_progress)?;
+    Ok(report)
+}
+
+fn copy_files(
+    files: &[PathBuf],
+    target_dir: &Path,
+    concurrency: usize,
+    on_progress: impl Fn(usize, usize) + Sync,
+) -> std::io::Result<usize> {
+    let total = files.len();
+    let next = AtomicUsize::new(0);
+    let copied = AtomicUsize::new(0);
+    let cancelled = AtomicBool::new(false);
+    let first_error: Mutex<Option<(usize, std::io::Error)>> = Mutex::new(None);
+
+    thread::scope(|scope| {
+        for _ in 0..concurrency.clamp(1, total.max(1)) {
+            scope.spawn(|| {
+                while !cancelled.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = files.get(index) else { break };
+                    let target = target_dir.join(path.file_name().unwrap_or_default());
+
+                    match fs::copy(path, &target) {
+                        Ok(_) => {
+                            let done = copied.fetch_add(1, Ordering::Relaxed) + 1;
+                            on_progress(done, total);
+                        }
+                        Err(e) => {
+                            cancelled.store(true, Ordering::Relaxed);
+                            let mut slot = first_error.lock().unwrap();
+                            if slot.as_ref().is_none_or(|(i, _)| index < *i) {
+                                let e = std::io::Error::new(
+                                    e.kind(),
+                                    format!("Failed to copy {:?}: {}", path, e),
+                                );
+                                *slot = Some((index, e));
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    match first_error.into_inner().unwrap() {
+        Some((_, e)) => Err(e),
+        None => Ok(copied.into_inner()),
+    }
+}
+
+/// Replaces `classes_dir` with the class files from `source_dir`. The new set is staged
+/// next to the old one and swapped in only once fully copied, so a failed copy leaves
+/// the previous docs in place.
+pub fn replace_classes(
+    source_dir: &Path,
+    classes_dir: &Path,
+    concurrency: usize,
+    source: &DocsSource,
+    version: Option<&str>,
+) -> std::io::Result<usize> {
+    let staging = classes_dir.with_extension("staging");
+    let previous = classes_dir.with_extension("previous");
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging)?;
+
+    let staged = copy_classes(source_dir, &staging, concurrency, |_, _| {}).and_then(|count| {
+        if count == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("No class XML files found in {:?}", source_dir),
+            ));
+        }
+        mark_source(&staging, source.label())?;
+        if let Some(version) = version {
+            fs::write(staging.join(DOCS_VERSION_MARKER), version)?;
+        }
+        Ok(count)
+    });
+    let count = match staged {
+        Ok(count) => count,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+
+    let _ = fs::remove_dir_all(&previous);
+    if classes_dir.exists() {
+        fs::rename(classes_dir, &previous)?;
+    }
+    if let Err(e) = fs::rename(&staging, classes_dir) {
+        let _ = fs::rename(&previous, classes_dir);
+        return Err(e);
+    }
+    let _ = fs::remove_dir_all(&previous);
+    Ok(count)
+}
+
+pub fn mark_source(classes_dir: &Path, source: &str) -> std::io::Result<()> {
+    fs::write(classes_dir.join(DOCS_SOURCE_MARKER), source)
+}
+
+pub fn count_classes(classes_dir: &Path) -> usize {
+    fs::read_dir(classes_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "xml"))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+pub fn status(classes_dir: &Path, configured: DocsSource) -> DocsStatus {
+    let read_marker = |name: &str| {
+        fs::read_to_string(classes_dir.join(name))
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+    let source = read_marker(DOCS_SOURCE_MARKER).unwrap_or_else(|| "unknown".to_string());
+
+    DocsStatus {
+        fallback: source == "fallback",
+        source,
+        configured,
+        class_count: count_classes(classes_dir),
+        version: read_marker(DOCS_VERSION_MARKER),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sync_copies_only_missing_and_changed_classes() {
+        let root = std::env::temp_dir().join(format!("godoty-docs-sync-{}", std::process::id()));
+        let (source, target) = (root.join("source"), root.join("target"));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        for (name, body) in [("Node.xml", "<node/>"), ("Node2D.xml", "<node2d/>")] {
+            fs::write(source.join(name), body).unwrap();
+        }
+        fs::write(source.join("Sprite2D.xml"), "<sprite/>").unwrap();
+        fs::write(source.join("README.md"), "not a class").unwrap();
+        fs::copy(source.join("Node.xml"), target.join("Node.xml")).unwrap();
+        fs::write(target.join("Node2D.xml"), "<old/>").unwrap();
+
+        let report = sync_classes(&source, &target, 2, |_, _| {}).unwrap();
+        let again = sync_classes(&source, &target, 2, |_, _| {}).unwrap();
+        let node2d = fs::read_to_string(target.join("Node2D.xml")).unwrap();
+        let readme_copied = target.join("README.md").exists();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            report,
+            SyncReport {
+                added: 1,
+                updated: 1,
+                skipped: 1
+            }
+        );
+        assert_eq!(again.skipped, 3);
+        assert_eq!(node2d, "<node2d/>");
+        assert!(!readme_copied);
+    }
+}
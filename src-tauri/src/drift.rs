@@ -0,0 +1,167 @@
+use crate::config::get_config_dir;
+use crate::docs;
+use crate::setup::{self, COPIED_RESOURCES};
+use crate::updater::{self, Updater};
+use crate::version;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// How an installed item compares to the copy shipped in the bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftState {
+    Pristine,
+    UserModified,
+    /// Replaced by a newer version than the bundle ships, e.g. by the updater.
+    Updated,
+    Missing,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DriftItem {
+    /// Path relative to the config dir.
+    pub name: String,
+    pub state: DriftState,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct DriftReport {
+    pub items: Vec<DriftItem>,
+    /// Items that are not `Pristine`.
+    pub drifted: usize,
+}
+
+/// Compares the config dir against the bundle without changing anything.
+pub fn detect<R: Runtime>(app: &AppHandle<R>) -> Result<DriftReport, String> {
+    let config_dir = get_config_dir(app).map_err(|e| e.to_string())?;
+    let mut items = Vec::new();
+
+    for (resource_path, target) in COPIED_RESOURCES {
+        let bundled = app
+            .path()
+            .resolve(resource_path, tauri::path::BaseDirectory::Resource)
+            .map_err(|e| e.to_string())?;
+        items.push(compare_files(target, &bundled, &config_dir.join(target)));
+    }
+
+    items.push(opencode_config_drift(app, &config_dir));
+    items.push(docs_drift(app, &config_dir));
+    items.push(binary_drift(app));
+
+    let drifted = items
+        .iter()
+        .filter(|i| i.state != DriftState::Pristine)
+        .count();
+    Ok(DriftReport { items, drifted })
+}
+
+fn compare_files(name: &str, bundled: &Path, installed: &Path) -> DriftItem {
+    let state = match (file_sha256(installed), file_sha256(bundled)) {
+        (None, _) => DriftState::Missing,
+        (Some(a), Some(b)) if a == b => DriftState::Pristine,
+        _ => DriftState::UserModified,
+    };
+    item(name, state, None)
+}
+
+/// opencode.json is templated at install time, so it is compared to the rendered bundle.
+fn opencode_config_drift<R: Runtime>(app: &AppHandle<R>, config_dir: &Path) -> DriftItem {
+    let name = "opencode.json";
+    let Ok(installed) = fs::read_to_string(config_dir.join(name)) else {
+        return item(name, DriftState::Missing, None);
+    };
+    match setup::render_opencode_config(app, config_dir) {
+        Ok(expected) if expected == installed => item(name, DriftState::Pristine, None),
+        Ok(_) => item(name, DriftState::UserModified, None),
+        Err(e) => item(name, DriftState::UserModified, Some(e.to_string())),
+    }
+}
+
+/// Summarised as a single item; the class set has hundreds of files.
+fn docs_drift<R: Runtime>(app: &AppHandle<R>, config_dir: &Path) -> DriftItem {
+    let name = "godot_docs/classes";
+    let classes_dir = config_dir.join(name);
+    let status = docs::status(&classes_dir, docs::DocsSource::Bundled);
+    if status.class_count == 0 {
+        return item(name, DriftState::Missing, None);
+    }
+    if status.source != "bundled" {
+        let detail = format!("{} docs installed", status.source);
+        return item(name, DriftState::UserModified, Some(detail));
+    }
+
+    let Ok(bundled_dir) = setup::bundled_docs_dir(app) else {
+        return item(name, DriftState::Pristine, None);
+    };
+    let Ok(entries) = fs::read_dir(&bundled_dir) else {
+        return item(name, DriftState::Pristine, None);
+    };
+
+    let (mut missing, mut modified) = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "xml") {
+            continue;
+        }
+        match file_sha256(&classes_dir.join(entry.file_name())) {
+            None => missing += 1,
+            Some(hash) if Some(&hash) != file_sha256(&path).as_ref() => modified += 1,
+            Some(_) => {}
+        }
+    }
+
+    match (missing, modified) {
+        (0, 0) => item(name, DriftState::Pristine, None),
+        _ => item(
+            name,
+            DriftState::UserModified,
+            Some(format!("{} missing, {} modified", missing, modified)),
+        ),
+    }
+}
+
+fn binary_drift<R: Runtime>(app: &AppHandle<R>) -> DriftItem {
+    let name = "bin/opencode-cli";
+    let updater = Updater::new(app);
+    let installed = match updater.get_sidecar_path() {
+        Ok(path) if path.exists() => path,
+        _ => return item(name, DriftState::Missing, None),
+    };
+    let bundled = match updater.find_bundled_binary() {
+        Ok(path) => path,
+        Err(e) => return item(name, DriftState::UserModified, Some(e)),
+    };
+    if file_sha256(&installed) == file_sha256(&bundled) {
+        return item(name, DriftState::Pristine, None);
+    }
+
+    let installed_version = updater::binary_version(&installed).unwrap_or_default();
+    let bundled_version = updater::binary_version(&bundled).unwrap_or_default();
+    let detail = Some(format!(
+        "installed {}, bundled {}",
+        installed_version, bundled_version
+    ));
+    match version::compare(&installed_version, &bundled_version) {
+        Some(Ordering::Greater) => item(name, DriftState::Updated, detail),
+        _ => item(name, DriftState::UserModified, detail),
+    }
+}
+
+fn item(name: &str, state: DriftState, detail: Option<String>) -> DriftItem {
+    DriftItem {
+        name: name.to_string(),
+        state,
+        detail,
+    }
+}
+
+fn file_sha256(path: &Path) -> Option<String> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+    Some(format!("{:x}", hasher.finalize()))
+}
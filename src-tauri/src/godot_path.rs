@@ -0,0 +1,192 @@
+use crate::settings;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Executable names looked up on `PATH`.
+const PATH_NAMES: &[&str] = &["godot", "godot4"];
+
+/// Where the Godot path handed to the sidecar came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GodotPathSource {
+    /// `GODOT_PATH` was set in the environment.
+    Env,
+    /// `godot_path` in `godoty.toml`.
+    Settings,
+    /// The only Godot install found in the usual locations.
+    Discovered,
+    /// Nothing set and no single install found.
+    NotFound,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GodotPath {
+    pub path: Option<String>,
+    pub source: GodotPathSource,
+    /// Installs found by discovery, listed so the user can pick one when there are several.
+    pub candidates: Vec<String>,
+}
+
+/// Resolves the Godot executable: `GODOT_PATH`, then the `godot_path` setting, then a
+/// search of common install locations that only counts when it finds exactly one.
+pub fn resolve<R: Runtime>(app: &AppHandle<R>) -> GodotPath {
+    if let Some(path) = std::env::var_os("GODOT_PATH").filter(|p| !p.is_empty()) {
+        return GodotPath {
+            path: Some(path.to_string_lossy().to_string()),
+            source: GodotPathSource::Env,
+            candidates: Vec::new(),
+        };
+    }
+    if let Some(path) = settings::current(app)
+        .godot_path
+        .filter(|p| !p.trim().is_empty())
+    {
+        return GodotPath {
+            path: Some(path),
+            source: GodotPathSource::Settings,
+            candidates: Vec::new(),
+        };
+    }
+
+    let home = app.path().home_dir().ok();
+    let found = unique_files(candidates(home.as_deref(), std::env::var_os("PATH")));
+    let candidates: Vec<String> = found
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    match candidates.as_slice() {
+        [only] => GodotPath {
+            path: Some(only.clone()),
+            source: GodotPathSource::Discovered,
+            candidates,
+        },
+        _ => GodotPath {
+            path: None,
+            source: GodotPathSource::NotFound,
+            candidates,
+        },
+    }
+}
+
+/// Possible Godot executables for this OS; not all of them exist.
+#[cfg_attr(windows, allow(unused_variables))]
+fn candidates(home: Option<&Path>, path_var: Option<OsString>) -> Vec<PathBuf> {
+    let mut found = search_path(path_var);
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut apps = vec![PathBuf::from("/Applications")];
+        apps.extend(home.map(|h| h.join("Applications")));
+        if let Some(home) = home {
+            apps.push(home.join("Library/Application Support/Steam/steamapps/common/Godot Engine"));
+        }
+        for dir in apps {
+            for app in ["Godot.app", "Godot_mono.app"] {
+                found.push(dir.join(app).join("Contents/MacOS/Godot"));
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        let mut dirs = Vec::new();
+        for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+            if let Some(root) = std::env::var_os(var).map(PathBuf::from) {
+                dirs.push(root.join("Godot"));
+                dirs.push(root.join("Steam/steamapps/common/Godot Engine"));
+            }
+        }
+        if let Some(local) = std::env::var_os("LOCALAPPDATA").map(PathBuf::from) {
+            dirs.push(local.join("Godot"));
+            dirs.push(local.join("Programs/Godot"));
+        }
+        for dir in dirs {
+            found.extend(godot_executables_in(&dir));
+        }
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        found.push(PathBuf::from(
+            "/var/lib/flatpak/exports/bin/org.godotengine.Godot",
+        ));
+        if let Some(home) = home {
+            found.push(home.join(".local/share/flatpak/exports/bin/org.godotengine.Godot"));
+            for steam in [".local/share/Steam", ".steam/steam"] {
+                let dir = home.join(steam).join("steamapps/common/Godot Engine");
+                found.extend(godot_executables_in(&dir));
+            }
+        }
+    }
+
+    found
+}
+
+/// `PATH_NAMES` found in the directories of `path_var`.
+fn search_path(path_var: Option<OsString>) -> Vec<PathBuf> {
+    let Some(path_var) = path_var else {
+        return Vec::new();
+    };
+    std::env::split_paths(&path_var)
+        .flat_map(|dir| {
+            PATH_NAMES
+                .iter()
+                .map(move |name| dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+        })
+        .collect()
+}
+
+/// Editor binaries in an install directory, e.g. `Godot_v4.3-stable_win64.exe`. Console
+/// wrappers are skipped since they would open a terminal window.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn godot_executables_in(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                return false;
+            };
+            let name = name.to_ascii_lowercase();
+            let is_exe = cfg!(not(windows)) || name.ends_with(".exe");
+            name.starts_with("godot") && is_exe && !name.contains("console")
+        })
+        .collect()
+}
+
+/// Existing files among `paths`, with duplicates reached through symlinks removed.
+fn unique_files(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|p| p.is_file())
+        .filter(|p| seen.insert(fs::canonicalize(p).unwrap_or_else(|_| p.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_godot_on_path_once_per_install() {
+        let dir = std::env::temp_dir().join(format!("godoty-godot-path-{}", std::process::id()));
+        let (bin, other) = (dir.join("bin"), dir.join("other"));
+        fs::create_dir_all(&bin).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        let exe = format!("godot{}", std::env::consts::EXE_SUFFIX);
+        fs::write(bin.join(&exe), "").unwrap();
+
+        let path_var = std::env::join_paths([&bin, &other, &bin]).unwrap();
+        let found = unique_files(search_path(Some(path_var)));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found, vec![bin.join(exe)]);
+    }
+}
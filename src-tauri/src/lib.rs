@@ -1,7 +1,21 @@
+mod cleanup;
 mod config;
+mod diagnostics;
+mod docs;
+mod drift;
+mod godot_path;
+mod migration;
+mod orphans;
+mod process;
+mod retention;
+mod settings;
 mod setup;
 mod sidecar;
+mod sidecar_log;
+mod update_plan;
 mod updater;
+mod version;
+mod watcher;
 mod commands;
 
 use tauri::Manager;
@@ -21,7 +35,33 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         .setup(|app| {
             app.manage(sidecar::SidecarState::default());
-            setup::init_config(app.handle())?;
+            app.manage(settings::SettingsState::default());
+            app.manage(updater::ReleaseCache::default());
+            app.manage(updater::UpdateGuard::default());
+            app.manage(watcher::ConfigWatcherState::default());
+            // Loaded first: init_config reads the docs copy concurrency.
+            let settings = settings::reload(app.handle());
+            let first_run = setup::init_config(app.handle())?;
+            app.manage(setup::FirstRun::new(first_run));
+            if settings.watch_config {
+                if let Err(e) = watcher::set_enabled(app.handle(), true) {
+                    eprintln!("[Watcher] Failed to start: {}", e);
+                }
+            }
+            let logs_dir = config::get_logs_dir(app.handle())?;
+            std::thread::spawn(move || {
+                let report = cleanup::sweep_update_temp(Some(cleanup::STALE_UPDATE_AGE));
+                if !report.removed.is_empty() {
+                    println!("[Cleanup] Startup sweep freed {} bytes", report.bytes_freed);
+                }
+                let report = retention::enforce(&logs_dir, &settings.log_retention);
+                if !report.removed.is_empty() {
+                    println!(
+                        "[Retention] Startup enforcement freed {} bytes",
+                        report.bytes_freed
+                    );
+                }
+            });
             sidecar::SidecarManager::start_sidecar(app.handle());
             Ok(())
         })
@@ -30,14 +70,44 @@ pub fn run() {
             commands::get_sidecar_version,
             commands::check_sidecar_update,
             commands::perform_sidecar_update,
-            commands::restart_sidecar
+            commands::restart_sidecar,
+            commands::cleanup_temp,
+            commands::get_settings,
+            commands::set_log_retention,
+            commands::enforce_retention,
+            commands::check_update_connectivity,
+            commands::get_docs_status,
+            commands::get_version_compatibility,
+            commands::reload_sidecar_config,
+            commands::stop_detached_sidecar,
+            commands::fix_sidecar_permissions,
+            commands::get_sidecar_output_buffer,
+            commands::get_expected_asset,
+            commands::set_docs_source,
+            commands::set_docs_dir,
+            commands::is_first_run,
+            commands::set_sidecar_bind_host,
+            commands::detect_drift,
+            commands::reload_settings,
+            commands::set_watch_config,
+            commands::list_orphaned_sidecars,
+            commands::kill_orphaned_sidecars,
+            commands::get_sidecar_port,
+            commands::get_sidecar_log_path,
+            commands::open_config_dir,
+            commands::open_config_subdir,
+            commands::get_godot_path,
+            commands::reinstall_sidecar,
+            commands::get_diagnostics
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|app_handle, event| match event {
-            tauri::RunEvent::ExitRequested { .. } => {
-                sidecar::SidecarManager::shutdown(app_handle);
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                // A detached sidecar is left running for the next launch to adopt.
+                if !sidecar::SidecarManager::is_detached() {
+                    sidecar::SidecarManager::shutdown(app_handle);
+                }
             }
-            _ => {}
         });
 }
@@ -0,0 +1,139 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Written to the config dir after a migration, naming the directory it came from.
+pub const MIGRATED_MARKER: &str = ".migrated";
+
+/// Top-level entries never carried over: the marker itself, a pid that belongs to a
+/// process from the old location, the sidecar binary (reinstalled from the bundle), old
+/// logs and regenerable caches.
+const SKIPPED: &[&str] = &[
+    MIGRATED_MARKER,
+    crate::sidecar::DETACHED_PID_FILE,
+    "bin",
+    "logs",
+    "cache",
+];
+
+/// Migration only runs while the config dir lacks this file, and a legacy directory only
+/// counts if it has it, not e.g. webview data Tauri keeps under the same path.
+const CONFIG_FILE: &str = "opencode.json";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct MigrationReport {
+    /// Files copied from the legacy directory.
+    pub copied: usize,
+    /// Files left alone because the config dir already had them.
+    pub kept: usize,
+}
+
+/// Directories earlier versions kept their config in: the app-identifier paths. The
+/// current default location is not one of them, so a portable or `GODOTY_CONFIG_DIR`
+/// profile never imports the default profile.
+fn legacy_dirs<R: Runtime>(app: &AppHandle<R>) -> Vec<PathBuf> {
+    let path = app.path();
+    [path.app_config_dir(), path.app_data_dir()]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Copies the first legacy directory holding a config into `config_dir`, once, and only
+/// while `config_dir` has no config of its own. Files already in `config_dir` are never
+/// overwritten, and the legacy directory is left in place.
+pub fn migrate<R: Runtime>(
+    app: &AppHandle<R>,
+    config_dir: &Path,
+) -> io::Result<Option<MigrationReport>> {
+    if config_dir.join(MIGRATED_MARKER).exists() || config_dir.join(CONFIG_FILE).exists() {
+        return Ok(None);
+    }
+    let current = fs::canonicalize(config_dir).unwrap_or_else(|_| config_dir.to_path_buf());
+    let Some(legacy) = legacy_dirs(app).into_iter().find(|dir| {
+        dir.join(CONFIG_FILE).is_file() && fs::canonicalize(dir).is_ok_and(|dir| dir != current)
+    }) else {
+        return Ok(None);
+    };
+
+    println!("[Setup] Migrating config from {:?}", legacy);
+    fs::create_dir_all(config_dir)?;
+    let mut report = MigrationReport::default();
+    copy_missing(&legacy, config_dir, true, &mut report)?;
+    fs::write(
+        config_dir.join(MIGRATED_MARKER),
+        legacy.to_string_lossy().as_bytes(),
+    )?;
+    println!(
+        "[Setup] Migrated {} files from {:?}, kept {} existing; the old directory was not removed",
+        report.copied, legacy, report.kept
+    );
+    Ok(Some(report))
+}
+
+/// Recursively copies files from `from` that don't exist under `to`. Symlinks are skipped.
+fn copy_missing(
+    from: &Path,
+    to: &Path,
+    top_level: bool,
+    report: &mut MigrationReport,
+) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if top_level && SKIPPED.iter().any(|s| name == *s) {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        let target = to.join(&name);
+        if file_type.is_dir() {
+            fs::create_dir_all(&target)?;
+            copy_missing(&entry.path(), &target, false, report)?;
+        } else if file_type.is_file() {
+            if target.exists() {
+                report.kept += 1;
+            } else {
+                fs::copy(entry.path(), &target)?;
+                report.copied += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_missing_files_without_clobbering() {
+        let dir = std::env::temp_dir().join(format!("godoty-migration-{}", std::process::id()));
+        let (legacy, current) = (dir.join("legacy"), dir.join("current"));
+        for d in ["data/storage", "cache", "bin", "logs"] {
+            fs::create_dir_all(legacy.join(d)).unwrap();
+        }
+        fs::create_dir_all(&current).unwrap();
+        fs::write(legacy.join("opencode.json"), "old").unwrap();
+        fs::write(legacy.join("data/storage/session.json"), "s").unwrap();
+        fs::write(legacy.join("cache/blob"), "c").unwrap();
+        fs::write(legacy.join("sidecar.pid"), "123").unwrap();
+        fs::write(legacy.join("bin/opencode-cli"), "old binary").unwrap();
+        fs::write(legacy.join("logs/sidecar.log"), "old log").unwrap();
+        fs::write(current.join("opencode.json"), "new").unwrap();
+
+        let mut report = MigrationReport::default();
+        copy_missing(&legacy, &current, true, &mut report).unwrap();
+        let config = fs::read_to_string(current.join("opencode.json")).unwrap();
+        let session = current.join("data/storage/session.json").exists();
+        let skipped = ["cache", "sidecar.pid", "bin", "logs"]
+            .iter()
+            .any(|name| current.join(name).exists());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report, MigrationReport { copied: 1, kept: 1 });
+        assert_eq!(config, "new");
+        assert!(session);
+        assert!(!skipped);
+    }
+}
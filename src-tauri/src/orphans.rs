@@ -0,0 +1,216 @@
+use crate::config::get_config_dir;
+use crate::process;
+use crate::sidecar::{SidecarManager, SidecarState, DETACHED_PID_FILE};
+use crate::updater::Updater;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// A process running our sidecar binary that this session does not track.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OrphanedSidecar {
+    pub pid: u32,
+    /// TCP ports the process is listening on.
+    pub ports: Vec<u16>,
+}
+
+/// Finds processes whose executable is our installed sidecar binary, excluding the child
+/// this session spawned and a detached sidecar recorded in the pid file. Ownership is
+/// decided by executable path, so an independent opencode install is never matched.
+pub fn list<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<OrphanedSidecar>, String> {
+    let sidecar_path = Updater::new(app)
+        .get_sidecar_path()
+        .map_err(|e| e.to_string())?;
+    let sidecar_path = fs::canonicalize(&sidecar_path).unwrap_or(sidecar_path);
+    let tracked = tracked_pids(app);
+
+    let orphans = running_executables()?
+        .into_iter()
+        .filter(|(pid, _)| *pid != std::process::id() && !tracked.contains(pid))
+        .filter(|(_, exe)| same_executable(exe, &sidecar_path))
+        .map(|(pid, _)| OrphanedSidecar {
+            pid,
+            ports: listening_ports(pid),
+        })
+        .collect();
+    Ok(orphans)
+}
+
+/// Terminates exactly the processes `list` reports. Returns the pids that were stopped.
+pub fn kill<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<u32>, String> {
+    let mut killed = Vec::new();
+    for orphan in list(app)? {
+        if SidecarManager::kill_pid(orphan.pid) {
+            println!("[Sidecar] Killed orphaned sidecar (PID {})", orphan.pid);
+            killed.push(orphan.pid);
+        } else {
+            eprintln!(
+                "[Sidecar] Failed to kill orphaned sidecar (PID {})",
+                orphan.pid
+            );
+        }
+    }
+    Ok(killed)
+}
+
+fn tracked_pids<R: Runtime>(app: &AppHandle<R>) -> Vec<u32> {
+    let mut pids = Vec::new();
+    if let Some(state) = app.try_state::<SidecarState>() {
+        if let Some(child) = state.child.lock().unwrap().as_ref() {
+            pids.push(child.id());
+        }
+    }
+    let detached = get_config_dir(app)
+        .ok()
+        .and_then(|dir| fs::read_to_string(dir.join(DETACHED_PID_FILE)).ok())
+        .and_then(|content| content.trim().parse::<u32>().ok());
+    pids.extend(detached);
+    pids
+}
+
+fn same_executable(exe: &Path, sidecar_path: &Path) -> bool {
+    exe == sidecar_path || fs::canonicalize(exe).is_ok_and(|exe| exe == sidecar_path)
+}
+
+/// Pid and executable path of every process we can inspect.
+#[cfg(target_os = "linux")]
+fn running_executables() -> Result<Vec<(u32, PathBuf)>, String> {
+    let entries = fs::read_dir("/proc").map_err(|e| e.to_string())?;
+    Ok(entries
+        .flatten()
+        .filter_map(|entry| {
+            let pid = entry.file_name().to_str()?.parse().ok()?;
+            let exe = fs::read_link(entry.path().join("exe")).ok()?;
+            // A binary replaced by an update still counts as ours.
+            let exe = match exe.to_str().and_then(|s| s.strip_suffix(" (deleted)")) {
+                Some(stripped) => PathBuf::from(stripped),
+                None => exe,
+            };
+            Some((pid, exe))
+        })
+        .collect())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn running_executables() -> Result<Vec<(u32, PathBuf)>, String> {
+    // On macOS `comm` is the full executable path.
+    let output = process::command("ps")
+        .args(["-A", "-o", "pid=,comm="])
+        .output()
+        .map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, exe) = line.trim().split_once(char::is_whitespace)?;
+            Some((pid.parse().ok()?, PathBuf::from(exe.trim())))
+        })
+        .collect())
+}
+
+#[cfg(windows)]
+fn running_executables() -> Result<Vec<(u32, PathBuf)>, String> {
+    let output = process::command("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_Process | Where-Object ExecutablePath | \
+             ForEach-Object { \"$($_.ProcessId) $($_.ExecutablePath)\" }",
+        ])
+        .output()
+        .map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (pid, exe) = line.trim().split_once(' ')?;
+            Some((pid.parse().ok()?, PathBuf::from(exe)))
+        })
+        .collect())
+}
+
+#[cfg(unix)]
+fn listening_ports(pid: u32) -> Vec<u16> {
+    let Ok(output) = process::command("lsof")
+        .args([
+            "-Pan",
+            "-p",
+            &pid.to_string(),
+            "-iTCP",
+            "-sTCP:LISTEN",
+            "-Fn",
+        ])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let mut ports: Vec<u16> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix('n'))
+        .filter_map(|addr| addr.rsplit(':').next()?.parse().ok())
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+#[cfg(windows)]
+fn listening_ports(pid: u32) -> Vec<u16> {
+    let Ok(output) = process::command("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let mut ports: Vec<u16> = parse_netstat_listeners(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|(_, owner)| *owner == pid)
+        .map(|(port, _)| port)
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// Extracts `(port, pid)` for each listening TCP socket in `netstat -ano` output.
+/// Listeners are recognised by their `:0` foreign address, since the state column is
+/// localised on non-English Windows.
+#[cfg_attr(not(windows), allow(dead_code))]
+pub(crate) fn parse_netstat_listeners(output: &str) -> Vec<(u16, u32)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            let [proto, local, foreign, _state, pid] = cols.as_slice() else {
+                return None;
+            };
+            if !proto.eq_ignore_ascii_case("TCP") || !foreign.ends_with(":0") {
+                return None;
+            }
+            let port = local.rsplit(':').next()?.parse().ok()?;
+            Some((port, pid.parse().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_netstat_listeners() {
+        let output = "
+Active Connections
+
+  Proto  Local Address          Foreign Address        State           PID
+  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1052
+  TCP    127.0.0.1:4096         0.0.0.0:0              LISTENING       8120
+  TCP    127.0.0.1:4096         127.0.0.1:51234        ESTABLISHED     8120
+  TCP    [::]:4096              [::]:0                 ABHÖREN         8120
+  TCP    [::1]:5000             [::]:0                 LISTENING       notapid
+  UDP    0.0.0.0:5353           *:*                                    2200
+";
+        assert_eq!(
+            parse_netstat_listeners(output),
+            vec![(135, 1052), (4096, 8120), (4096, 8120)]
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use std::ffi::OsStr;
+use std::process::Command;
+
+/// Stops Windows from opening a console window for a child process.
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// `Command::new` for every process we spawn. On Windows a release build has no console
+/// of its own, so each child would otherwise flash a new one; elsewhere this is a plain
+/// `Command`.
+pub fn command<S: AsRef<OsStr>>(program: S) -> Command {
+    #[allow(unused_mut)]
+    let mut command = Command::new(program);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
@@ -0,0 +1,106 @@
+use crate::cleanup::dir_size;
+use crate::config::{ACTIVE_LOG_FILE, CRASH_REPORTS_DIR, SNAPSHOTS_DIR};
+use crate::settings::LogRetention;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct RetentionReport {
+    pub removed: Vec<String>,
+    pub bytes_freed: u64,
+}
+
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Deletes old rotated logs, crash reports and snapshots under `logs_dir`, oldest first,
+/// until every limit in `policy` is met. The active log and the newest crash report are
+/// never deleted.
+pub fn enforce(logs_dir: &Path, policy: &LogRetention) -> RetentionReport {
+    let mut report = RetentionReport::default();
+    if !logs_dir.is_dir() {
+        return report;
+    }
+
+    // Rotated logs: keep the newest `max_rotated_logs`, then trim by total size.
+    let active_size = fs::metadata(logs_dir.join(ACTIVE_LOG_FILE)).map_or(0, |m| m.len());
+    let mut rotated: Vec<Entry> = list_newest_first(logs_dir)
+        .into_iter()
+        .filter(|e| {
+            e.path.extension().is_some_and(|ext| ext == "log")
+                && e.path.file_name().is_some_and(|n| n != ACTIVE_LOG_FILE)
+        })
+        .collect();
+
+    while rotated.len() > policy.max_rotated_logs {
+        remove(rotated.pop().unwrap(), &mut report);
+    }
+    let mut total = active_size + rotated.iter().map(|e| e.size).sum::<u64>();
+    while total > policy.max_total_log_bytes {
+        let Some(oldest) = rotated.pop() else { break };
+        total -= oldest.size;
+        remove(oldest, &mut report);
+    }
+
+    let mut crashes = list_newest_first(&logs_dir.join(CRASH_REPORTS_DIR));
+    while crashes.len() > policy.max_crash_reports.max(1) {
+        remove(crashes.pop().unwrap(), &mut report);
+    }
+
+    let max_age = Duration::from_secs(policy.max_snapshot_age_days * 24 * 60 * 60);
+    for snapshot in list_newest_first(&logs_dir.join(SNAPSHOTS_DIR)) {
+        let expired = SystemTime::now()
+            .duration_since(snapshot.modified)
+            .is_ok_and(|age| age > max_age);
+        if expired {
+            remove(snapshot, &mut report);
+        }
+    }
+
+    report
+}
+
+fn list_newest_first(dir: &Path) -> Vec<Entry> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut list: Vec<Entry> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = fs::symlink_metadata(entry.path()).ok()?;
+            let size = if metadata.is_dir() {
+                dir_size(&entry.path())
+            } else {
+                metadata.len()
+            };
+            Some(Entry {
+                path: entry.path(),
+                size,
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            })
+        })
+        .collect();
+    list.sort_by_key(|e| std::cmp::Reverse(e.modified));
+    list
+}
+
+fn remove(entry: Entry, report: &mut RetentionReport) {
+    let result = if entry.path.is_dir() {
+        fs::remove_dir_all(&entry.path)
+    } else {
+        fs::remove_file(&entry.path)
+    };
+    match result {
+        Ok(()) => {
+            report.bytes_freed += entry.size;
+            report
+                .removed
+                .push(entry.path.to_string_lossy().to_string());
+        }
+        Err(e) => eprintln!("[Retention] Failed to remove {:?}: {}", entry.path, e),
+    }
+}
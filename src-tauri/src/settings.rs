@@ -0,0 +1,134 @@
+use crate::config::get_config_dir;
+use crate::docs::DocsSource;
+use crate::watcher;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// User-editable settings file, stored in the config directory.
+pub const SETTINGS_FILE: &str = "godoty.toml";
+
+/// Upper bound for the default docs copy concurrency; more threads only contend on disk.
+const MAX_DEFAULT_DOCS_COPY_CONCURRENCY: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub log_retention: LogRetention,
+    /// Worker threads used to copy the Godot class docs; `1` copies sequentially.
+    pub docs_copy_concurrency: usize,
+    pub docs_source: DocsSource,
+    /// IP address the sidecar listens on.
+    pub sidecar_bind_host: String,
+    /// Required for a non-loopback `sidecar_bind_host`, which exposes the sidecar to the
+    /// network; without it the sidecar stays on loopback.
+    pub allow_network_access: bool,
+    /// Apply external edits to `opencode.json` and `godoty.toml` without a restart.
+    pub watch_config: bool,
+    /// Godot executable passed to the sidecar as `GODOT_PATH`; overrides discovery.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub godot_path: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+        Self {
+            log_retention: LogRetention::default(),
+            docs_copy_concurrency: cpus.min(MAX_DEFAULT_DOCS_COPY_CONCURRENCY),
+            docs_source: DocsSource::default(),
+            sidecar_bind_host: "127.0.0.1".to_string(),
+            allow_network_access: false,
+            watch_config: false,
+            godot_path: None,
+        }
+    }
+}
+
+/// Limits applied to the `logs` directory by `retention::enforce`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogRetention {
+    /// Combined size of the active and rotated sidecar logs.
+    pub max_total_log_bytes: u64,
+    /// Rotated log files kept besides the active one; also how many the sidecar log
+    /// rotates through.
+    pub max_rotated_logs: usize,
+    /// Crash reports kept; the most recent one is always kept.
+    pub max_crash_reports: usize,
+    /// Snapshots older than this are deleted.
+    pub max_snapshot_age_days: u64,
+}
+
+impl Default for LogRetention {
+    fn default() -> Self {
+        Self {
+            max_total_log_bytes: 20 * 1024 * 1024,
+            max_rotated_logs: 5,
+            max_crash_reports: 10,
+            max_snapshot_age_days: 14,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SettingsState {
+    pub settings: Mutex<Settings>,
+}
+
+/// Reads `godoty.toml`, falling back to defaults when it is missing or invalid.
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Settings {
+    let path = match get_config_dir(app) {
+        Ok(dir) => dir.join(SETTINGS_FILE),
+        Err(e) => {
+            eprintln!("[Settings] Failed to resolve config dir: {}", e);
+            return Settings::default();
+        }
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+            eprintln!("[Settings] Ignoring invalid {:?}: {}", path, e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    }
+}
+
+/// Re-reads `godoty.toml` into the managed state and returns the result.
+pub fn reload<R: Runtime>(app: &AppHandle<R>) -> Settings {
+    let settings = load(app);
+    if let Some(state) = app.try_state::<SettingsState>() {
+        *state.settings.lock().unwrap() = settings.clone();
+    }
+    settings
+}
+
+/// Returns the settings currently in effect.
+pub fn current<R: Runtime>(app: &AppHandle<R>) -> Settings {
+    match app.try_state::<SettingsState>() {
+        Some(state) => state.settings.lock().unwrap().clone(),
+        None => load(app),
+    }
+}
+
+/// Applies `change` to the current settings, persists them and updates the managed state.
+pub fn update<R: Runtime>(
+    app: &AppHandle<R>,
+    change: impl FnOnce(&mut Settings),
+) -> Result<Settings, Box<dyn std::error::Error + Send + Sync>> {
+    let mut settings = current(app);
+    change(&mut settings);
+
+    let config_dir = get_config_dir(app)?;
+    fs::create_dir_all(&config_dir)?;
+    let content = toml::to_string_pretty(&settings)?;
+    fs::write(config_dir.join(SETTINGS_FILE), &content)?;
+    watcher::record_write(app, SETTINGS_FILE, content.as_bytes());
+
+    if let Some(state) = app.try_state::<SettingsState>() {
+        *state.settings.lock().unwrap() = settings.clone();
+    }
+    Ok(settings)
+}
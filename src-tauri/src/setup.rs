@@ -1,23 +1,92 @@
-use crate::config::get_config_dir;
+use crate::config::{get_config_dir, require_utf8_path};
+use crate::docs;
+use crate::migration;
+use crate::settings;
+use crate::watcher::{self, OPENCODE_CONFIG_FILE};
 use std::fs;
-use std::path::Path;
-use tauri::{AppHandle, Manager};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 
-pub fn init_config(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+/// Payload of the `setup-progress` event emitted while first-run resources are copied.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SetupProgress {
+    pub stage: &'static str,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Resources copied verbatim from the bundle, as (resource path, path in the config dir).
+pub const COPIED_RESOURCES: &[(&str, &str)] = &[
+    ("resources/antigravity.json", "antigravity.json"),
+    // MCP server bundles
+    (
+        "resources/mcp-servers/godot/server.js",
+        "mcp-servers/godot/server.js",
+    ),
+    (
+        "resources/mcp-servers/godot-doc/doc-server.js",
+        "mcp-servers/godot-doc/doc-server.js",
+    ),
+    // GDScript files
+    (
+        "resources/mcp-servers/godot/scripts/godot_operations.gd",
+        "mcp-servers/godot/scripts/godot_operations.gd",
+    ),
+    (
+        "resources/mcp-servers/godot/scripts/viewport_capture.gd",
+        "mcp-servers/godot/scripts/viewport_capture.gd",
+    ),
+];
+
+/// Records the app version and config dir `opencode.json` was last templated for.
+const OPENCODE_CONFIG_MARKER: &str = "opencode.json.version";
+
+/// Written to the config dir once `init_config` has completed successfully.
+const INITIALIZED_MARKER: &str = ".initialized";
+
+/// Present in any config dir an earlier version set up, including those from before
+/// `INITIALIZED_MARKER` existed.
+const EXISTING_CONFIG_FILES: &[&str] = &[OPENCODE_CONFIG_FILE, settings::SETTINGS_FILE];
+
+/// Whether this launch is the first to initialize the config dir, decided before
+/// `init_config` writes its marker, and whether `first-run` has been sent yet.
+pub struct FirstRun(pub bool, pub AtomicBool);
+
+impl FirstRun {
+    pub fn new(first_run: bool) -> Self {
+        Self(first_run, AtomicBool::new(false))
+    }
+}
+
+/// Initializes the config dir and returns whether this was the first run.
+pub fn init_config(app_handle: &AppHandle) -> Result<bool, Box<dyn std::error::Error>> {
     let config_dir = get_config_dir(app_handle)?;
     println!("[Setup] Initializing config in: {:?}", config_dir);
+    let marker_path = config_dir.join(INITIALIZED_MARKER);
+    let initialized = marker_path.exists();
+    let first_run = !initialized
+        && !EXISTING_CONFIG_FILES
+            .iter()
+            .any(|file| config_dir.join(file).exists());
 
     if !config_dir.exists() {
         println!("[Setup] Creating config dir");
         fs::create_dir_all(&config_dir)?;
     }
 
+    // Before the bundled resources are copied, so a migrated opencode.json is kept.
+    if let Err(e) = migration::migrate(app_handle, &config_dir) {
+        eprintln!("[Setup] Failed to migrate legacy config: {}", e);
+    }
+
     // Create required subdirectories
     let dirs = vec![
         "godot_docs",
         "godot_docs/classes",
         "data",
         "cache",
+        "logs",
         "mcp-servers/godot/scripts",
         "mcp-servers/godot-doc",
     ];
@@ -29,44 +98,28 @@ pub fn init_config(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Err
         }
     }
 
-    // Copy simple resources (antigravity.json is just copied directly)
-    copy_resource(
-        app_handle,
-        "resources/antigravity.json",
-        &config_dir.join("antigravity.json"),
-    )?;
-
-    // Copy opencode.json with path templating
-    copy_opencode_config(app_handle, &config_dir)?;
-
-    // Copy MCP server bundles
-    copy_resource(
-        app_handle,
-        "resources/mcp-servers/godot/server.js",
-        &config_dir.join("mcp-servers/godot/server.js"),
-    )?;
-    copy_resource(
-        app_handle,
-        "resources/mcp-servers/godot-doc/doc-server.js",
-        &config_dir.join("mcp-servers/godot-doc/doc-server.js"),
-    )?;
+    // Copy simple resources, MCP server bundles and GDScript files
+    for (resource_path, target) in COPIED_RESOURCES {
+        copy_resource(app_handle, resource_path, &config_dir.join(target))?;
+    }
 
-    // Copy GDScript files
-    copy_resource(
-        app_handle,
-        "resources/mcp-servers/godot/scripts/godot_operations.gd",
-        &config_dir.join("mcp-servers/godot/scripts/godot_operations.gd"),
-    )?;
-    copy_resource(
-        app_handle,
-        "resources/mcp-servers/godot/scripts/viewport_capture.gd",
-        &config_dir.join("mcp-servers/godot/scripts/viewport_capture.gd"),
-    )?;
+    // Copy opencode.json with path templating; start_sidecar reports a non-UTF-8 config dir
+    match require_utf8_path(&config_dir) {
+        Ok(_) => copy_opencode_config(app_handle, &config_dir)?,
+        Err(e) => eprintln!("[Setup] Skipping opencode.json: {}", e),
+    }
 
     // Copy Godot docs XML class files
     copy_godot_docs(app_handle, &config_dir)?;
 
-    Ok(())
+    if !initialized {
+        if first_run {
+            println!("[Setup] First run, writing {}", INITIALIZED_MARKER);
+        }
+        fs::write(&marker_path, env!("CARGO_PKG_VERSION"))?;
+    }
+
+    Ok(first_run)
 }
 
 /// Copy a single resource file from the app bundle to the target path.
@@ -100,29 +153,65 @@ fn copy_resource(
 }
 
 /// Copy opencode.json and replace {{CONFIG_DIR}} placeholders with the actual config directory path.
-fn copy_opencode_config(
-    app_handle: &AppHandle,
+///
+/// An existing file that differs from the template is kept when the marker shows this app
+/// version already templated it for this config dir, since the difference is then a user
+/// edit. Otherwise (new app version, moved config dir) it must be re-templated, and the
+/// old file is backed up to `opencode.json.bak-<unix time>` first.
+pub fn copy_opencode_config<R: Runtime>(
+    app_handle: &AppHandle<R>,
     config_dir: &Path,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let resource_path = "resources/opencode.json";
     let target_path = config_dir.join("opencode.json");
+    let marker_path = config_dir.join(OPENCODE_CONFIG_MARKER);
+    let marker = format!(
+        "{}\n{}",
+        env!("CARGO_PKG_VERSION"),
+        require_utf8_path(config_dir)?
+    );
 
     println!("[Setup] Copying opencode.json with path templating");
 
-    let resource_full_path = app_handle
-        .path()
-        .resolve(resource_path, tauri::path::BaseDirectory::Resource)?;
+    let templated = render_opencode_config(app_handle, config_dir)?;
+    if let Ok(existing) = fs::read_to_string(&target_path) {
+        if existing == templated {
+            fs::write(&marker_path, marker)?;
+            return Ok(());
+        }
+        if fs::read_to_string(&marker_path).is_ok_and(|m| m == marker) {
+            println!("[Setup] opencode.json has local edits, keeping it");
+            return Ok(());
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let backup = config_dir.join(format!("opencode.json.bak-{}", timestamp));
+        fs::copy(&target_path, &backup)?;
+        println!("[Setup] Backed up modified opencode.json to {:?}", backup);
+    }
 
-    if resource_full_path.exists() {
-        let content = fs::read_to_string(&resource_full_path)?;
-        let config_dir_str = config_dir.to_string_lossy();
-        let templated = content.replace("{{CONFIG_DIR}}", &config_dir_str);
-        fs::write(&target_path, templated)?;
-        println!(
-            "[Setup] opencode.json written with config_dir: {}",
-            config_dir_str
-        );
-    } else {
+    fs::write(&target_path, &templated)?;
+    watcher::record_write(app_handle, OPENCODE_CONFIG_FILE, templated.as_bytes());
+    fs::write(&marker_path, marker)?;
+    println!(
+        "[Setup] opencode.json written with config_dir: {}",
+        require_utf8_path(config_dir)?
+    );
+
+    Ok(())
+}
+
+/// The bundled opencode.json with `{{CONFIG_DIR}}` replaced, as it would be written.
+pub fn render_opencode_config<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    config_dir: &Path,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let resource_full_path = app_handle.path().resolve(
+        "resources/opencode.json",
+        tauri::path::BaseDirectory::Resource,
+    )?;
+
+    if !resource_full_path.exists() {
         return Err(format!(
             "opencode.json resource not found at: {:?}",
             resource_full_path
@@ -130,61 +219,211 @@ fn copy_opencode_config(
         .into());
     }
 
-    Ok(())
+    let content = fs::read_to_string(&resource_full_path)?;
+    let config_dir_str = require_utf8_path(config_dir)?;
+    Ok(content.replace("{{CONFIG_DIR}}", config_dir_str))
 }
 
-fn copy_godot_docs(
-    app_handle: &AppHandle,
-    config_dir: &Path,
-) -> Result<(), Box<dyn std::error::Error>> {
+/// Directory holding the Godot class docs shipped with the app.
+pub fn bundled_docs_dir<R: Runtime>(
+    app_handle: &AppHandle<R>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
     let resource_file_path = app_handle.path().resolve(
         "resources/godot_docs/classes/@GlobalScope.xml",
         tauri::path::BaseDirectory::Resource,
     )?;
 
-    let resource_dir_path = resource_file_path
+    Ok(resource_file_path
         .parent()
-        .ok_or("Could not find parent directory of @GlobalScope.xml")?;
+        .ok_or("Could not find parent directory of @GlobalScope.xml")?
+        .to_path_buf())
+}
 
-    let target_dir = config_dir.join("godot_docs/classes");
-    fs::create_dir_all(&target_dir)?;
+/// The docs source in effect: `GODOTY_DOCS_DIR` if set, otherwise the configured one.
+pub fn effective_docs_source<R: Runtime>(app_handle: &AppHandle<R>) -> docs::DocsSource {
+    docs::env_docs_source().unwrap_or_else(|| settings::current(app_handle).docs_source)
+}
 
-    let marker = target_dir.join(".version");
-    let current_version = env!("CARGO_PKG_VERSION");
+/// Refreshes the classes dir from the `GODOTY_DOCS_DIR` override on every launch. The
+/// copy lands where `GODOT_DOC_DIR` already points the sidecar.
+fn sync_env_docs(
+    source: &docs::DocsSource,
+    target_dir: &Path,
+    app_handle: &AppHandle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let docs::DocsSource::Custom { path } = source else {
+        return Ok(());
+    };
+    docs::validate_docs_dir(path)?;
+    let concurrency = settings::current(app_handle).docs_copy_concurrency;
+    // Switching from another set replaces it wholesale so none of its classes linger.
+    if docs::status(target_dir, source.clone()).source != source.label() {
+        let count = docs::replace_classes(path, target_dir, concurrency, source, None)?;
+        println!(
+            "[Setup] Installed {} Godot doc XML files from GODOTY_DOCS_DIR {:?}",
+            count, path
+        );
+        return Ok(());
+    }
+    let report = docs::sync_classes(path, target_dir, concurrency, |_, _| {})?;
+    println!(
+        "[Setup] Synced Godot docs from GODOTY_DOCS_DIR {:?}: {} added, {} updated",
+        path, report.added, report.updated
+    );
+    Ok(())
+}
 
-    if marker.exists() {
-        if let Ok(stamped) = fs::read_to_string(&marker) {
-            if stamped.trim() == current_version {
-                println!(
-                    "[Setup] Godot docs already up-to-date (v{}), skipping copy",
-                    current_version
+/// Repopulates `godot_docs/classes` from `source`, keeping the current docs if the copy
+/// fails. Returns the number of class files installed.
+pub fn apply_docs_source<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    source: &docs::DocsSource,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let docs_dir = get_config_dir(app_handle)?.join("godot_docs");
+    let (source_dir, version) = match source {
+        docs::DocsSource::Bundled => (
+            bundled_docs_dir(app_handle)?,
+            Some(env!("CARGO_PKG_VERSION").to_string()),
+        ),
+        docs::DocsSource::Custom { path } => {
+            docs::validate_docs_dir(path)?;
+            (path.clone(), None)
+        }
+        docs::DocsSource::Downloaded { version } => {
+            // The version becomes a path component, so keep it to a plain name.
+            let valid = !version.is_empty()
+                && version
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+                && version != "."
+                && version != "..";
+            if !valid {
+                return Err(format!("Invalid docs version: {:?}", version).into());
+            }
+            let dir = docs_dir.join(docs::DOWNLOADED_DOCS_DIR).join(version);
+            if !dir.is_dir() {
+                return Err(
+                    format!("Docs for version {} have not been downloaded", version).into(),
                 );
-                return Ok(());
             }
+            (dir, Some(version.clone()))
+        }
+    };
+
+    if !source_dir.is_dir() {
+        return Err(format!("Docs source directory not found: {:?}", source_dir).into());
+    }
+
+    let concurrency = settings::current(app_handle).docs_copy_concurrency;
+    let count = docs::replace_classes(
+        &source_dir,
+        &docs_dir.join("classes"),
+        concurrency,
+        source,
+        version.as_deref(),
+    )?;
+    println!(
+        "[Setup] Installed {} Godot doc XML files from {} source {:?}",
+        count,
+        source.label(),
+        source_dir
+    );
+    Ok(count)
+}
+
+fn copy_godot_docs(
+    app_handle: &AppHandle,
+    config_dir: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let target_dir = config_dir.join("godot_docs/classes");
+    fs::create_dir_all(&target_dir)?;
+
+    if let Some(source) = docs::env_docs_source() {
+        match sync_env_docs(&source, &target_dir, app_handle) {
+            Ok(()) => return Ok(()),
+            Err(e) => eprintln!("[Setup] Ignoring GODOTY_DOCS_DIR: {}", e),
         }
     }
 
+    // A user-selected set is only replaced through `set_docs_source`.
+    let source = settings::current(app_handle).docs_source;
+    if source != docs::DocsSource::Bundled {
+        println!(
+            "[Setup] Using {} Godot docs, skipping bundled copy",
+            source.label()
+        );
+        return Ok(());
+    }
+
+    let resource_dir = bundled_docs_dir(app_handle)?;
+    let resource_dir_path = resource_dir.as_path();
+
+    let marker = target_dir.join(docs::DOCS_VERSION_MARKER);
+    let current_version = env!("CARGO_PKG_VERSION");
+
     if resource_dir_path.exists() && resource_dir_path.is_dir() {
-        let mut count = 0u32;
-        for entry in fs::read_dir(&resource_dir_path)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.extension().map_or(false, |ext| ext == "xml") {
-                if let Some(filename) = path.file_name() {
-                    let target = target_dir.join(filename);
-                    fs::copy(&path, &target)?;
-                    count += 1;
-                }
-            }
+        // Fast path: the marker only counts if the copy it stamps looks complete, since an
+        // interrupted copy leaves a marker from an earlier run behind.
+        let stamped = fs::read_to_string(&marker).is_ok_and(|s| s.trim() == current_version);
+        let source_count = docs::list_classes(resource_dir_path)?.len();
+        if stamped && docs::count_classes(&target_dir) >= source_count {
+            println!(
+                "[Setup] Godot docs already up-to-date (v{}), skipping copy",
+                current_version
+            );
+            return Ok(());
+        }
+
+        let concurrency = settings::current(app_handle).docs_copy_concurrency;
+        // Left over from a `GODOTY_DOCS_DIR` override that has since been removed.
+        let on_disk = docs::status(&target_dir, docs::DocsSource::Bundled).source;
+        if on_disk == "custom" || on_disk == "downloaded" {
+            let count = docs::replace_classes(
+                resource_dir_path,
+                &target_dir,
+                concurrency,
+                &docs::DocsSource::Bundled,
+                Some(current_version),
+            )?;
+            println!("[Setup] Restored {} bundled Godot doc XML files", count);
+            return Ok(());
         }
-        println!("[Setup] Copied {} Godot doc XML files", count);
+
+        let report = docs::sync_classes(
+            resource_dir_path,
+            &target_dir,
+            concurrency,
+            |done, total| {
+                let _ = app_handle.emit(
+                    "setup-progress",
+                    SetupProgress {
+                        stage: "godot_docs",
+                        completed: done,
+                        total,
+                    },
+                );
+            },
+        )?;
+        println!(
+            "[Setup] Synced Godot doc XML files: {} added, {} updated, {} unchanged ({} threads)",
+            report.added, report.updated, report.skipped, concurrency
+        );
         fs::write(&marker, current_version)?;
+        docs::mark_source(&target_dir, "bundled")?;
+    } else if docs::count_classes(&target_dir) == 0 {
+        // Degrade to the embedded core classes rather than leaving the doc server empty.
+        // No version marker is written, so the bundled set is retried on the next launch.
+        eprintln!(
+            "[Setup] Godot docs resource dir not found at: {:?}, writing fallback docs",
+            resource_dir_path
+        );
+        let count = docs::write_fallback(&target_dir)?;
+        println!("[Setup] Wrote {} fallback Godot doc XML files", count);
     } else {
-        return Err(format!(
-            "Godot docs resource dir not found at: {:?}",
+        eprintln!(
+            "[Setup] Godot docs resource dir not found at: {:?}, keeping existing docs",
             resource_dir_path
-        )
-        .into());
+        );
     }
 
     Ok(())
@@ -1,38 +1,279 @@
-use crate::config::get_config_dir;
+use crate::config::{get_config_dir, get_logs_dir, require_utf8_path};
+use crate::godot_path;
+use crate::process;
+use crate::settings;
+use crate::setup::{copy_opencode_config, FirstRun};
+use crate::sidecar_log::SidecarLog;
 use crate::updater::Updater;
-use tauri::Manager;
-use std::sync::{Arc, Mutex};
-use std::process::{Command, Stdio, Child};
+use tauri::{Emitter, Manager};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::process::{Stdio, Child};
 use std::time::Duration;
+use std::fs;
 use std::io::{BufRead, BufReader};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::thread;
 
 pub struct SidecarState {
     pub child: Arc<Mutex<Option<Child>>>,
+    /// First lines printed by the most recent spawn, kept after the process exits.
+    pub startup_output: Arc<Mutex<Vec<OutputLine>>>,
+    /// Port the sidecar was started on, which differs from `GODOTY_PORT` when that port
+    /// was held by another application.
+    pub port: Arc<Mutex<Option<u16>>>,
+    /// Set by `shutdown` so the exit it causes isn't treated as a crash.
+    pub intentional_shutdown: Arc<AtomicBool>,
+    /// Consecutive crashes, reset once a sidecar stays up for `STABLE_UPTIME`.
+    pub crash_count: Arc<AtomicU32>,
 }
 
 impl Default for SidecarState {
     fn default() -> Self {
         Self {
             child: Arc::new(Mutex::new(None)),
+            startup_output: Arc::new(Mutex::new(Vec::new())),
+            port: Arc::new(Mutex::new(None)),
+            intentional_shutdown: Arc::new(AtomicBool::new(false)),
+            crash_count: Arc::new(AtomicU32::new(0)),
         }
     }
 }
 
+impl SidecarState {
+    /// Counts an exit of the monitored child as a crash, unless `shutdown` caused it.
+    fn record_exit(&self, exit_code: Option<i32>, uptime: Duration) -> Option<SidecarCrash> {
+        if self.intentional_shutdown.load(Ordering::SeqCst) {
+            return None;
+        }
+        if uptime >= STABLE_UPTIME {
+            self.crash_count.store(0, Ordering::SeqCst);
+        }
+        let attempt = self.crash_count.fetch_add(1, Ordering::SeqCst) + 1;
+        Some(SidecarCrash {
+            exit_code,
+            attempt,
+            will_restart: attempt <= MAX_RESPAWN_ATTEMPTS,
+        })
+    }
+}
+
+/// Number of lines kept in `SidecarState::startup_output`.
+const STARTUP_OUTPUT_LINES: usize = 200;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OutputLine {
+    /// `stdout` or `stderr`.
+    pub stream: &'static str,
+    pub line: String,
+}
+
+fn capture_startup_line(buffer: &Mutex<Vec<OutputLine>>, stream: &'static str, line: &str) {
+    let mut buffer = buffer.lock().unwrap();
+    if buffer.len() < STARTUP_OUTPUT_LINES {
+        buffer.push(OutputLine {
+            stream,
+            line: line.to_string(),
+        });
+    }
+}
+
+/// Per-request timeout of the HTTP readiness probe.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Health check polls made after a normal spawn, 500ms apart.
+const HEALTH_ATTEMPTS: u32 = 30;
+
+/// How often the spawned child is polled for an unexpected exit.
+const MONITOR_INTERVAL: Duration = Duration::from_millis(500);
+/// Respawns attempted for consecutive crashes before giving up.
+const MAX_RESPAWN_ATTEMPTS: u32 = 5;
+/// Delay before the first respawn, doubled for each consecutive crash up to the cap.
+const RESPAWN_BASE_DELAY: Duration = Duration::from_secs(1);
+const RESPAWN_MAX_DELAY: Duration = Duration::from_secs(16);
+/// A sidecar that ran this long before exiting starts a fresh crash count.
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+
+/// Payload of the `sidecar-crashed` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SidecarCrash {
+    pub exit_code: Option<i32>,
+    /// Consecutive crashes, including this one.
+    pub attempt: u32,
+    pub will_restart: bool,
+}
+
+/// Payload of the `sidecar-restarted` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SidecarRestarted {
+    pub attempt: u32,
+}
+
+/// Port used when `GODOTY_PORT` is unset or invalid.
+const DEFAULT_PORT: u16 = 4096;
+/// Lowest accepted `GODOTY_PORT`; privileged ports are rejected.
+const MIN_PORT: u16 = 1024;
+
+/// Ports after `GODOTY_PORT` tried when it is held by something other than our sidecar.
+const PORT_SEARCH_RANGE: u16 = 100;
+
+/// Records the pid of a detached sidecar so a later session can stop it.
+pub(crate) const DETACHED_PID_FILE: &str = "sidecar.pid";
+
+/// Endpoint that drops the sidecar's cached instance so its config is re-read.
+const RELOAD_ENDPOINT: &str = "/instance/dispose";
+/// Endpoint that asks the sidecar to flush its state and exit.
+const SHUTDOWN_ENDPOINT: &str = "/shutdown";
+/// How long a graceful shutdown may take before the process is killed.
+const GRACEFUL_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How `reload_config` applied the new configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReloadMethod {
+    Http,
+    Restart,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ReloadOutcome {
+    pub method: ReloadMethod,
+    /// False when the sidecar had to be restarted.
+    pub live: bool,
+}
+
+/// Why the sidecar binary could not be started, so the UI can offer the matching fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnFailureReason {
+    /// Offer a reinstall.
+    BinaryMissing,
+    /// Offer a permissions fix.
+    NotExecutable,
+    /// Built for another OS or CPU; offer a reinstall.
+    WrongArchitecture,
+    /// The config dir can't be passed to the sidecar intact; it has to be moved.
+    InvalidConfigPath,
+    Other,
+}
+
+/// Payload of the `sidecar-spawn-failed` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SpawnFailure {
+    pub reason: SpawnFailureReason,
+    pub message: String,
+    pub binary_path: String,
+    pub os_error: String,
+}
+
+impl SpawnFailure {
+    fn from_io(error: &std::io::Error, binary_path: &std::path::Path) -> Self {
+        // ENOEXEC is 8 on Linux and macOS; Windows reports ERROR_BAD_EXE_FORMAT (193).
+        #[cfg(unix)]
+        const BAD_EXECUTABLE: i32 = 8;
+        #[cfg(windows)]
+        const BAD_EXECUTABLE: i32 = 193;
+
+        let (reason, message) = match error.kind() {
+            std::io::ErrorKind::NotFound => (
+                SpawnFailureReason::BinaryMissing,
+                "Sidecar binary is missing, try reinstalling it",
+            ),
+            std::io::ErrorKind::PermissionDenied => (
+                SpawnFailureReason::NotExecutable,
+                "Sidecar binary is not executable",
+            ),
+            _ if error.raw_os_error() == Some(BAD_EXECUTABLE) => (
+                SpawnFailureReason::WrongArchitecture,
+                "Sidecar binary was built for the wrong architecture",
+            ),
+            _ => (
+                SpawnFailureReason::Other,
+                "Sidecar binary could not be started",
+            ),
+        };
+
+        Self {
+            reason,
+            message: message.to_string(),
+            binary_path: binary_path.to_string_lossy().to_string(),
+            os_error: error.to_string(),
+        }
+    }
+}
+
+/// How the sidecar is judged ready, from `GODOTY_READINESS_MODE`.
+#[derive(Debug, Clone, PartialEq)]
+enum ReadinessMode {
+    /// `GET /health` returns 200 (default).
+    Health,
+    /// `GET <path>` returns 200, for sidecars without a `/health` route.
+    Endpoint(String),
+    /// Accepting a TCP connection is enough.
+    Tcp,
+}
+
+impl ReadinessMode {
+    fn from_env() -> Self {
+        let Ok(value) = std::env::var("GODOTY_READINESS_MODE") else {
+            return ReadinessMode::Health;
+        };
+        match value.trim() {
+            "" | "health" => ReadinessMode::Health,
+            "tcp" => ReadinessMode::Tcp,
+            mode => match mode.strip_prefix("endpoint:") {
+                Some(path) if !path.trim().is_empty() => {
+                    let path = path.trim();
+                    if path.starts_with('/') {
+                        ReadinessMode::Endpoint(path.to_string())
+                    } else {
+                        ReadinessMode::Endpoint(format!("/{}", path))
+                    }
+                }
+                _ => {
+                    eprintln!(
+                        "[Sidecar] Unknown GODOTY_READINESS_MODE '{}', using health",
+                        mode
+                    );
+                    ReadinessMode::Health
+                }
+            },
+        }
+    }
+}
+
+/// Outcome of a single readiness probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    /// Nothing accepted the connection, or it didn't answer in time.
+    Unreachable,
+    /// Listening, but the probe returned this HTTP status.
+    Unhealthy(u16),
+}
+
+/// Payload of the `sidecar-network-exposed` warning event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NetworkExposure {
+    pub host: String,
+    pub port: u16,
+    pub message: String,
+}
+
 pub struct SidecarManager;
 
 impl SidecarManager {
     #[allow(dead_code)]
-    fn cleanup_stale_sidecar(port: &str) {
+    fn cleanup_stale_sidecar(port: u16) {
         use std::net::TcpStream;
 
         println!("[Sidecar] Cleaning up stale sidecar instances...");
 
-        let current_pid = std::process::id();
-
         #[cfg(unix)]
         {
-            if let Ok(output) = Command::new("ps")
+            let current_pid = std::process::id();
+            if let Ok(output) = process::command("ps")
                 .args(["-A", "-o", "pid,comm"])
                 .output()
             {
@@ -47,7 +288,7 @@ impl SidecarManager {
                             if let Ok(pid) = pid_str.parse::<u32>() {
                                 if pid != current_pid {
                                     println!("[Sidecar] Found stale process '{}' (PID {}), killing...", comm, pid);
-                                    let _ = Command::new("kill").arg(pid.to_string()).output();
+                                    let _ = process::command("kill").arg(pid.to_string()).output();
                                 }
                             }
                         }
@@ -57,17 +298,6 @@ impl SidecarManager {
             }
         }
 
-        #[cfg(windows)]
-        {
-             let _ = Command::new("taskkill")
-                .args(["/F", "/IM", "opencode-cli*", "/T"])
-                .output();
-             let _ = Command::new("taskkill")
-                .args(["/F", "/IM", "opencode*", "/T"])
-                .output();
-             thread::sleep(Duration::from_millis(500));
-        }
-
         let addr = format!("127.0.0.1:{}", port);
         let sock_addr: std::net::SocketAddr = match addr.parse() {
             Ok(a) => a,
@@ -82,7 +312,7 @@ impl SidecarManager {
 
         #[cfg(unix)]
         {
-            let output = match Command::new("lsof")
+            let output = match process::command("lsof")
                 .args(["-t", "-i", &format!(":{}", port), "-sTCP:LISTEN"])
                 .output()
             {
@@ -101,14 +331,14 @@ impl SidecarManager {
                     continue;
                 }
 
-                if let Ok(ps_output) = Command::new("ps")
+                if let Ok(ps_output) = process::command("ps")
                     .args(["-p", pid, "-o", "comm="])
                     .output()
                 {
                     let comm = String::from_utf8_lossy(&ps_output.stdout);
                     if comm.contains("opencode") {
                         println!("[Sidecar] Killing orphaned sidecar (PID {})", pid);
-                        let _ = Command::new("kill").arg(pid).output();
+                        let _ = process::command("kill").arg(pid).output();
                         killed = true;
                     } else {
                         eprintln!(
@@ -127,64 +357,217 @@ impl SidecarManager {
 
         #[cfg(windows)]
         {
-            eprintln!("[Sidecar] Port {} is occupied; please close the process manually", port);
+            let output = match process::command("netstat").args(["-ano", "-p", "TCP"]).output() {
+                Ok(o) => o,
+                Err(e) => {
+                    eprintln!("[Sidecar] Failed to run netstat: {}", e);
+                    return;
+                }
+            };
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let mut pids: Vec<u32> = crate::orphans::parse_netstat_listeners(&stdout)
+                .into_iter()
+                .filter(|(listening, _)| *listening == port)
+                .map(|(_, pid)| pid)
+                .collect();
+            pids.sort_unstable();
+            pids.dedup();
+
+            let mut killed = false;
+            for pid in pids {
+                if Self::is_sidecar_pid(pid) {
+                    println!("[Sidecar] Killing orphaned sidecar (PID {})", pid);
+                    killed |= Self::kill_pid(pid);
+                } else {
+                    eprintln!(
+                        "[Sidecar] Port {} held by non-sidecar process (PID {}), skipping",
+                        port, pid
+                    );
+                }
+            }
+
+            if killed {
+                thread::sleep(Duration::from_millis(1000));
+            }
         }
     }
 
-    fn is_sidecar_running(port: &str) -> bool {
-        use std::io::{Read, Write};
-        use std::net::TcpStream;
+    fn is_sidecar_running(addr: &str) -> bool {
+        Self::health_status(addr) == HealthStatus::Healthy
+    }
 
-        let addr = format!("127.0.0.1:{}", port);
-        let sock_addr: std::net::SocketAddr = match addr.parse() {
-            Ok(a) => a,
-            Err(_) => return false,
+    /// Probes `addr` (`host:port`) according to `GODOTY_READINESS_MODE`.
+    fn health_status(addr: &str) -> HealthStatus {
+        let path = match ReadinessMode::from_env() {
+            ReadinessMode::Tcp => {
+                let reachable = addr.parse::<std::net::SocketAddr>().is_ok_and(|a| {
+                    std::net::TcpStream::connect_timeout(&a, Duration::from_millis(100)).is_ok()
+                });
+                return if reachable {
+                    HealthStatus::Healthy
+                } else {
+                    HealthStatus::Unreachable
+                };
+            }
+            ReadinessMode::Health => "/health".to_string(),
+            ReadinessMode::Endpoint(path) => path,
         };
 
-        if let Ok(mut stream) = TcpStream::connect_timeout(&sock_addr, Duration::from_millis(100)) {
-            stream.set_read_timeout(Some(Duration::from_millis(500))).ok();
-            stream.set_write_timeout(Some(Duration::from_millis(500))).ok();
+        let client = match reqwest::blocking::Client::builder()
+            .connect_timeout(Duration::from_millis(200))
+            .timeout(HEALTH_TIMEOUT)
+            .build()
+        {
+            Ok(c) => c,
+            Err(_) => return HealthStatus::Unreachable,
+        };
+        match client.get(format!("http://{}{}", addr, path)).send() {
+            Ok(resp) if resp.status().is_success() => HealthStatus::Healthy,
+            Ok(resp) => HealthStatus::Unhealthy(resp.status().as_u16()),
+            Err(_) => HealthStatus::Unreachable,
+        }
+    }
 
-            let request = format!(
-                "GET /health HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nConnection: close\r\n\r\n",
-                port
-            );
+    /// Startup output captured from the most recent spawn, even if the process has exited.
+    pub fn startup_output<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Vec<OutputLine> {
+        app.try_state::<SidecarState>()
+            .map(|state| state.startup_output.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
 
-            if stream.write_all(request.as_bytes()).is_ok() {
-                let mut buf = [0u8; 1024];
-                if let Ok(n) = stream.read(&mut buf) {
-                    if n > 0 {
-                        let response = String::from_utf8_lossy(&buf[..n]);
-                        if response.contains("HTTP/1.1 200") || response.contains("HTTP/1.0 200") {
-                            return true;
-                        }
-                    }
-                }
+    /// With `GODOTY_DETACHED_SIDECAR` set, the sidecar outlives the app and is adopted by
+    /// the next launch through the health-check reuse path.
+    pub fn is_detached() -> bool {
+        std::env::var("GODOTY_DETACHED_SIDECAR")
+            .is_ok_and(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+    }
+
+    /// The configured `GODOTY_PORT`, read once so an invalid value is only reported once.
+    fn port() -> u16 {
+        static PORT: OnceLock<u16> = OnceLock::new();
+        *PORT.get_or_init(|| {
+            let Ok(raw) = std::env::var("GODOTY_PORT") else {
+                return DEFAULT_PORT;
+            };
+            parse_port(&raw).unwrap_or_else(|| {
+                eprintln!(
+                    "[Sidecar] Invalid GODOTY_PORT '{}', expected {}-65535; using {}",
+                    raw, MIN_PORT, DEFAULT_PORT
+                );
+                DEFAULT_PORT
+            })
+        })
+    }
+
+    /// The port the sidecar was last started on, or the configured one before any start.
+    pub fn active_port<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> u16 {
+        app.try_state::<SidecarState>()
+            .and_then(|state| *state.port.lock().unwrap())
+            .unwrap_or_else(Self::port)
+    }
+
+    /// Records the port the sidecar is on, telling the frontend with `sidecar-port-changed`
+    /// when it moved.
+    fn set_active_port<R: tauri::Runtime>(app: &tauri::AppHandle<R>, port: u16) {
+        if let Some(state) = app.try_state::<SidecarState>() {
+            let previous = state.port.lock().unwrap().replace(port);
+            if previous.is_some_and(|p| p != port) {
+                let _ = app.emit("sidecar-port-changed", port);
             }
         }
-        false
+    }
+
+    /// Keeps `start` if it can be bound, otherwise picks the next free port within
+    /// `PORT_SEARCH_RANGE`.
+    fn select_port(host: IpAddr, start: u16) -> Option<u16> {
+        if Self::port_is_free(host, start) {
+            return Some(start);
+        }
+        let end = start.saturating_add(PORT_SEARCH_RANGE);
+        let chosen = (start.saturating_add(1)..=end).find(|&p| Self::port_is_free(host, p))?;
+        println!(
+            "[Sidecar] Port {} is held by another process, using {} instead",
+            start, chosen
+        );
+        Some(chosen)
+    }
+
+    fn port_is_free(host: IpAddr, port: u16) -> bool {
+        std::net::TcpListener::bind((host, port)).is_ok()
+    }
+
+    /// The configured `sidecar_bind_host`, falling back to loopback when it isn't a valid
+    /// IP or exposes the sidecar to the network without `allow_network_access`.
+    fn bind_host<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> IpAddr {
+        let settings = settings::current(app);
+        let host = match settings.sidecar_bind_host.trim().parse::<IpAddr>() {
+            Ok(host) => host,
+            Err(_) => {
+                eprintln!(
+                    "[Sidecar] Invalid sidecar_bind_host '{}', using 127.0.0.1",
+                    settings.sidecar_bind_host
+                );
+                return IpAddr::V4(Ipv4Addr::LOCALHOST);
+            }
+        };
+        if !host.is_loopback() && !settings.allow_network_access {
+            eprintln!(
+                "[Sidecar] Not binding to {} without allow_network_access, using 127.0.0.1",
+                host
+            );
+            return IpAddr::V4(Ipv4Addr::LOCALHOST);
+        }
+        host
+    }
+
+    /// Address to reach a sidecar bound to `host`; wildcard binds are reached via loopback.
+    fn connect_addr(host: IpAddr, port: u16) -> String {
+        match host {
+            IpAddr::V4(ip) if ip.is_unspecified() => format!("{}:{}", Ipv4Addr::LOCALHOST, port),
+            IpAddr::V4(ip) => format!("{}:{}", ip, port),
+            IpAddr::V6(ip) if ip.is_unspecified() => format!("[{}]:{}", Ipv6Addr::LOCALHOST, port),
+            IpAddr::V6(ip) => format!("[{}]:{}", ip, port),
+        }
     }
 
     pub fn start_sidecar<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
-        let port = std::env::var("GODOTY_PORT").unwrap_or_else(|_| "4096".to_string());
+        let port = Self::port();
+        let host = Self::bind_host(app);
+        // A sidecar started earlier may be on a fallback port rather than the configured one.
+        let mut known_ports = vec![Self::active_port(app), port];
+        known_ports.dedup();
+        let running = known_ports
+            .into_iter()
+            .find(|&p| Self::is_sidecar_running(&Self::connect_addr(host, p)));
 
-        if Self::is_sidecar_running(&port) {
-            println!("[Sidecar] Found existing healthy instance on port {}, reusing it.", port);
+        if let Some(running_port) = running {
+            let addr = Self::connect_addr(host, running_port);
+            println!("[Sidecar] Found existing healthy instance at {}, reusing it.", addr);
+            Self::set_active_port(app, running_port);
             
-            let port_clone = port.clone();
+            let addr_clone = addr.clone();
             let app_clone = app.clone();
-            tauri::async_runtime::spawn(async move {
-                Self::wait_for_healthy(&port_clone);
-                if let Some(main_window) = app_clone.get_webview_window("main") {
-                    println!("[Sidecar] Showing main window");
-                    let _ = main_window.show();
-                }
+            // Blocking: the health probe uses reqwest's blocking client.
+            tauri::async_runtime::spawn_blocking(move || {
+                Self::wait_for_healthy(&addr_clone, HEALTH_ATTEMPTS);
+                Self::show_main_window(&app_clone);
             });
             return;
         }
         
         #[cfg(not(debug_assertions))]
-        Self::cleanup_stale_sidecar(&port);
+        Self::cleanup_stale_sidecar(port);
+
+        let Some(port) = Self::select_port(host, port) else {
+            eprintln!(
+                "[Sidecar] Port {} and the next {} ports are all in use",
+                port, PORT_SEARCH_RANGE
+            );
+            return;
+        };
+        Self::set_active_port(app, port);
+        let addr = Self::connect_addr(host, port);
 
         let config_dir = get_config_dir(app).expect("Failed to get config dir");
         println!("[Sidecar] Starting with config dir: {:?}", config_dir);
@@ -200,38 +583,124 @@ impl SidecarManager {
             }
         };
 
+        let compat_app = app.clone();
+        thread::spawn(move || {
+            let compat = match Updater::new(&compat_app).get_compatibility() {
+                Ok(compat) => compat,
+                Err(e) => {
+                    eprintln!("[Sidecar] Compatibility check failed: {}", e);
+                    return;
+                }
+            };
+            if !compat.compatible {
+                eprintln!("[Sidecar] Warning: {}", compat.message);
+                let _ = compat_app.emit("sidecar-incompatible", compat);
+            }
+        });
+
+        if let Err(e) = crate::updater::fix_permissions(&sidecar_path) {
+            eprintln!("[Sidecar] Failed to check sidecar permissions: {}", e);
+        }
+
+        if let Err(message) = require_utf8_path(&config_dir) {
+            eprintln!("[Sidecar] {}", message);
+            let failure = SpawnFailure {
+                reason: SpawnFailureReason::InvalidConfigPath,
+                message,
+                binary_path: sidecar_path.to_string_lossy().to_string(),
+                os_error: String::new(),
+            };
+            let _ = app.emit("sidecar-spawn-failed", failure);
+            return;
+        }
+
         println!("[Sidecar] Spawning sidecar from {:?}", sidecar_path);
 
-        let mut command = Command::new(sidecar_path);
+        let mut command = process::command(&sidecar_path);
         command
-            .args(["serve", "--port", &port])
-            .env("OPENCODE_CONFIG_FILE", opencode_config_path.to_string_lossy().to_string())
-            .env("OPENCODE_CONFIG_DIR", config_dir.to_string_lossy().to_string())
-            .env("OPENCODE_DATA_DIR", config_dir.join("data").to_string_lossy().to_string())
-            .env("XDG_CONFIG_HOME", config_dir.to_string_lossy().to_string())
-            .env("XDG_DATA_HOME", config_dir.join("data").to_string_lossy().to_string())
-            .env("XDG_CACHE_HOME", config_dir.join("cache").to_string_lossy().to_string())
-            .env("GODOT_DOC_DIR", godot_doc_dir.to_string_lossy().to_string())
+            .args([
+                "serve",
+                "--port",
+                &port.to_string(),
+                "--hostname",
+                &host.to_string(),
+            ])
+            .env("OPENCODE_CONFIG_FILE", &opencode_config_path)
+            .env("OPENCODE_CONFIG_DIR", &config_dir)
+            .env("OPENCODE_DATA_DIR", config_dir.join("data"))
+            .env("XDG_CONFIG_HOME", &config_dir)
+            .env("XDG_DATA_HOME", config_dir.join("data"))
+            .env("XDG_CACHE_HOME", config_dir.join("cache"))
+            .env("GODOT_DOC_DIR", &godot_doc_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        if let Ok(godot_path) = std::env::var("GODOT_PATH") {
-            println!("[Sidecar] Forwarding GODOT_PATH: {}", godot_path);
-            command.env("GODOT_PATH", godot_path);
+        let detached = Self::is_detached();
+        if detached {
+            println!("[Sidecar] Spawning detached; it will keep running after Godoty exits");
+            // Pipes to our reader threads would break when the app exits.
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+            #[cfg(unix)]
+            {
+                use std::os::unix::process::CommandExt;
+                command.process_group(0);
+            }
+            #[cfg(windows)]
+            {
+                use std::os::windows::process::CommandExt;
+                const DETACHED_PROCESS: u32 = 0x0000_0008;
+                const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+                // Replaces CREATE_NO_WINDOW; a detached process has no console either way.
+                command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+            }
+        }
+
+        let godot = godot_path::resolve(app);
+        match &godot.path {
+            Some(path) => {
+                println!("[Sidecar] Using GODOT_PATH ({:?}): {}", godot.source, path);
+                command.env("GODOT_PATH", path);
+            }
+            None if godot.candidates.is_empty() => {
+                eprintln!("[Sidecar] GODOT_PATH is not set and no Godot install was found")
+            }
+            None => eprintln!(
+                "[Sidecar] GODOT_PATH is not set and several Godot installs were found: {}",
+                godot.candidates.join(", ")
+            ),
         }
 
         match command.spawn() {
             Ok(mut child) => {
+                if detached {
+                    if let Err(e) =
+                        fs::write(config_dir.join(DETACHED_PID_FILE), child.id().to_string())
+                    {
+                        eprintln!("[Sidecar] Failed to record detached sidecar pid: {}", e);
+                    }
+                }
+
                 let stdout = child.stdout.take();
                 let stderr = child.stderr.take();
 
+                let startup_output = match app.try_state::<SidecarState>() {
+                    Some(state) => state.startup_output.clone(),
+                    None => Arc::new(Mutex::new(Vec::new())),
+                };
+                startup_output.lock().unwrap().clear();
+                let log = Self::open_log(app).map(Arc::new);
+
                 if let Some(stdout) = stdout {
+                    let startup_output = startup_output.clone();
+                    let log = log.clone();
                     thread::spawn(move || {
                         let reader = BufReader::new(stdout);
-                        for line in reader.lines() {
-                            if let Ok(l) = line {
-                                println!("[Sidecar Output]: {}", l);
+                        for l in reader.lines().map_while(Result::ok) {
+                            capture_startup_line(&startup_output, "stdout", &l);
+                            if let Some(log) = &log {
+                                log.write_line("stdout", &l);
                             }
+                            println!("[Sidecar Output]: {}", l);
                         }
                     });
                 }
@@ -239,69 +708,415 @@ impl SidecarManager {
                 if let Some(stderr) = stderr {
                     thread::spawn(move || {
                         let reader = BufReader::new(stderr);
-                        for line in reader.lines() {
-                            if let Ok(l) = line {
-                                eprintln!("[Sidecar Error]: {}", l);
+                        for l in reader.lines().map_while(Result::ok) {
+                            capture_startup_line(&startup_output, "stderr", &l);
+                            if let Some(log) = &log {
+                                log.write_line("stderr", &l);
                             }
+                            eprintln!("[Sidecar Error]: {}", l);
                         }
                     });
                 }
 
                 if let Some(state) = app.try_state::<SidecarState>() {
+                    let pid = child.id();
                     let mut child_lock = state.child.lock().unwrap();
                     *child_lock = Some(child);
+                    state.intentional_shutdown.store(false, Ordering::SeqCst);
                     println!("[Sidecar] Process spawned and stored in state");
+                    let monitor_app = app.clone();
+                    thread::spawn(move || Self::monitor_child(monitor_app, pid));
                 } else {
                     eprintln!("[Sidecar] Failed to get SidecarState - process will be orphaned!");
                 }
             }
             Err(e) => {
-                eprintln!("[Sidecar] Failed to spawn sidecar: {}", e);
+                let failure = SpawnFailure::from_io(&e, &sidecar_path);
+                eprintln!(
+                    "[Sidecar] Failed to spawn sidecar: {} ({:?}: {})",
+                    failure.message, sidecar_path, e
+                );
+                let _ = app.emit("sidecar-spawn-failed", failure);
                 return;
             }
         }
 
-        let port_clone = port.clone();
+        if !host.is_loopback() {
+            let warning = NetworkExposure {
+                host: host.to_string(),
+                port,
+                message: format!(
+                    "The sidecar is reachable from your network on {}:{}. Anyone who can \
+                     reach this address can control Godoty's agent.",
+                    host, port
+                ),
+            };
+            eprintln!("[Sidecar] WARNING: {}", warning.message);
+            let _ = app.emit("sidecar-network-exposed", warning);
+        }
+
         let app_clone = app.clone();
-        tauri::async_runtime::spawn(async move {
-            Self::wait_for_healthy(&port_clone);
-            if let Some(main_window) = app_clone.get_webview_window("main") {
-                println!("[Sidecar] Showing main window");
-                let _ = main_window.show();
-            }
+        tauri::async_runtime::spawn_blocking(move || {
+            Self::wait_for_healthy(&addr, HEALTH_ATTEMPTS);
+            Self::show_main_window(&app_clone);
         });
     }
 
-    fn wait_for_healthy(port: &str) {
+    /// Shows the main window, sending `first-run` the first time on a first run; the
+    /// webview isn't listening yet while the app is still being set up.
+    fn show_main_window<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+        if let Some(main_window) = app.get_webview_window("main") {
+            println!("[Sidecar] Showing main window");
+            let _ = main_window.show();
+        }
+        let announce = app
+            .try_state::<FirstRun>()
+            .is_some_and(|state| state.0 && !state.1.swap(true, Ordering::SeqCst));
+        if announce {
+            let _ = app.emit("first-run", ());
+        }
+    }
+
+    fn open_log<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<SidecarLog> {
+        let result = get_logs_dir(app)
+            .map_err(|e| e.to_string())
+            .and_then(|dir| {
+                let rotated_logs = settings::current(app).log_retention.max_rotated_logs;
+                SidecarLog::open(&dir, rotated_logs).map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(log) => Some(log),
+            Err(e) => {
+                eprintln!("[Sidecar] Failed to open sidecar log: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Polls the health check every 500ms, up to `max_attempts` times after the first.
+    fn wait_for_healthy(addr: &str, max_attempts: u32) -> bool {
         let mut attempts = 0;
+        let mut last = None;
         loop {
-            if Self::is_sidecar_running(port) {
-                println!("[Sidecar] Health check passed on port {}", port);
-                break;
+            let status = Self::health_status(addr);
+            if status == HealthStatus::Healthy {
+                println!("[Sidecar] Health check passed at {}", addr);
+                return true;
+            }
+            if last != Some(status) {
+                match status {
+                    HealthStatus::Unhealthy(code) => {
+                        println!("[Sidecar] Sidecar at {} is up but returned {}", addr, code)
+                    }
+                    _ => println!("[Sidecar] Nothing answering at {} yet", addr),
+                }
+                last = Some(status);
             }
             attempts += 1;
-            if attempts > 30 {
-                eprintln!("[Sidecar] Timed out waiting for sidecar health check");
-                break;
+            if attempts > max_attempts {
+                match status {
+                    HealthStatus::Unhealthy(code) => eprintln!(
+                        "[Sidecar] Timed out waiting for sidecar health check (last status {})",
+                        code
+                    ),
+                    _ => eprintln!(
+                        "[Sidecar] Timed out waiting for sidecar health check (nothing listening)"
+                    ),
+                }
+                return false;
             }
             thread::sleep(Duration::from_millis(500));
         }
     }
 
-    pub fn shutdown<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
-        if let Some(state) = app.try_state::<SidecarState>() {
+    /// Waits for the sidecar at the configured address to pass its health check.
+    pub fn wait_until_healthy<R: tauri::Runtime>(
+        app: &tauri::AppHandle<R>,
+        max_attempts: u32,
+    ) -> bool {
+        let addr = Self::connect_addr(Self::bind_host(app), Self::active_port(app));
+        Self::wait_for_healthy(&addr, max_attempts)
+    }
+
+    /// A single readiness probe of the sidecar at the configured address.
+    pub fn probe_health<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> HealthStatus {
+        let addr = Self::connect_addr(Self::bind_host(app), Self::active_port(app));
+        Self::health_status(&addr)
+    }
+
+    /// Polls the child spawned as `pid` and respawns it with backoff if it exits without
+    /// `shutdown` being called. Ends once that child is replaced or taken out of state.
+    fn monitor_child<R: tauri::Runtime>(app: tauri::AppHandle<R>, pid: u32) {
+        let started = std::time::Instant::now();
+        let status = loop {
+            thread::sleep(MONITOR_INTERVAL);
+            let Some(state) = app.try_state::<SidecarState>() else {
+                return;
+            };
             let mut child_lock = state.child.lock().unwrap();
-            if let Some(mut child) = child_lock.take() {
-                println!("[Sidecar] Shutting down process...");
-                let _ = child.kill();
+            let child = match child_lock.as_mut() {
+                Some(child) if child.id() == pid => child,
+                _ => return,
+            };
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    *child_lock = None;
+                    break status;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("[Sidecar] Failed to poll sidecar process: {}", e);
+                    return;
+                }
             }
+        };
+
+        let state = app.state::<SidecarState>();
+        let Some(crash) = state.record_exit(status.code(), started.elapsed()) else {
+            return;
+        };
+        let attempt = crash.attempt;
+        eprintln!("[Sidecar] Sidecar exited unexpectedly ({})", status);
+        let will_restart = crash.will_restart;
+        let _ = app.emit("sidecar-crashed", crash);
+        if !will_restart {
+            eprintln!(
+                "[Sidecar] Giving up after {} consecutive crashes",
+                MAX_RESPAWN_ATTEMPTS
+            );
+            return;
+        }
+
+        let delay = RESPAWN_BASE_DELAY
+            .saturating_mul(1 << (attempt - 1))
+            .min(RESPAWN_MAX_DELAY);
+        println!("[Sidecar] Respawning in {:?} (attempt {})", delay, attempt);
+        thread::sleep(delay);
+        // The user may have stopped or restarted the sidecar during the backoff.
+        if state.intentional_shutdown.load(Ordering::SeqCst)
+            || state.child.lock().unwrap().is_some()
+        {
+            return;
+        }
+        Self::start_sidecar(&app);
+        if state.child.lock().unwrap().is_some() {
+            let _ = app.emit("sidecar-restarted", SidecarRestarted { attempt });
         }
     }
 
+    /// Asks the sidecar to exit on its own so it can finish writing its state, and only
+    /// kills it if it is still running after `GRACEFUL_SHUTDOWN_TIMEOUT`.
+    pub fn shutdown<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+        let Some(state) = app.try_state::<SidecarState>() else {
+            return;
+        };
+        state.intentional_shutdown.store(true, Ordering::SeqCst);
+        // Taken out first so the lock isn't held while waiting for the exit.
+        let child = state.child.lock().unwrap().take();
+        let Some(mut child) = child else {
+            return;
+        };
+
+        println!("[Sidecar] Shutting down process...");
+        let addr = Self::connect_addr(Self::bind_host(app), Self::active_port(app));
+        if Self::request_graceful_shutdown(&addr, &child)
+            && Self::wait_for_exit(&mut child, GRACEFUL_SHUTDOWN_TIMEOUT)
+        {
+            println!("[Sidecar] Sidecar exited gracefully");
+            return;
+        }
+        eprintln!("[Sidecar] Sidecar did not exit gracefully, killing it");
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    /// POSTs to the shutdown endpoint, falling back to SIGTERM on Unix. Returns whether
+    /// the request was delivered.
+    fn request_graceful_shutdown(addr: &str, child: &Child) -> bool {
+        let requested = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(2))
+            .build()
+            .is_ok_and(|client| {
+                client
+                    .post(format!("http://{}{}", addr, SHUTDOWN_ENDPOINT))
+                    .send()
+                    .is_ok_and(|resp| resp.status().is_success())
+            });
+        if requested {
+            return true;
+        }
+
+        #[cfg(unix)]
+        let signalled = process::command("kill")
+            .args(["-TERM", &child.id().to_string()])
+            .status()
+            .is_ok_and(|s| s.success());
+        #[cfg(not(unix))]
+        let signalled = {
+            let _ = child;
+            false
+        };
+        signalled
+    }
+
+    fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+        let deadline = std::time::Instant::now() + timeout;
+        while std::time::Instant::now() < deadline {
+            if matches!(child.try_wait(), Ok(Some(_))) {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        false
+    }
+
     pub fn restart_sidecar<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
         println!("[Sidecar] Restarting...");
+        if let Some(state) = app.try_state::<SidecarState>() {
+            state.crash_count.store(0, Ordering::SeqCst);
+        }
         Self::shutdown(app);
         thread::sleep(Duration::from_millis(500));
         Self::start_sidecar(app);
     }
+
+    /// Stops a detached sidecar recorded in the pid file, including one started by a
+    /// previous session. Returns whether a process was stopped.
+    pub fn stop_detached<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<bool, String> {
+        let pid_file = get_config_dir(app)
+            .map_err(|e| e.to_string())?
+            .join(DETACHED_PID_FILE);
+        let pid = match fs::read_to_string(&pid_file) {
+            Ok(content) => content
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid pid in {:?}", pid_file))?,
+            Err(_) => return Ok(false),
+        };
+
+        // Drop our handle if this session spawned it, so shutdown doesn't act on a dead pid.
+        if let Some(state) = app.try_state::<SidecarState>() {
+            let mut child_lock = state.child.lock().unwrap();
+            if child_lock.as_ref().is_some_and(|c| c.id() == pid) {
+                *child_lock = None;
+            }
+        }
+
+        let stopped = Self::is_sidecar_pid(pid) && Self::kill_pid(pid);
+        let _ = fs::remove_file(&pid_file);
+        if stopped {
+            println!("[Sidecar] Stopped detached sidecar (PID {})", pid);
+        }
+        Ok(stopped)
+    }
+
+    /// Guards against pid reuse: only processes that look like our sidecar are killed.
+    fn is_sidecar_pid(pid: u32) -> bool {
+        #[cfg(unix)]
+        let output = process::command("ps")
+            .args(["-p", &pid.to_string(), "-o", "comm="])
+            .output();
+        #[cfg(windows)]
+        let output = process::command("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+            .output();
+
+        output.is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains("opencode"))
+    }
+
+    pub(crate) fn kill_pid(pid: u32) -> bool {
+        #[cfg(unix)]
+        let status = process::command("kill").arg(pid.to_string()).status();
+        #[cfg(windows)]
+        let status = process::command("taskkill")
+            .args(["/F", "/PID", &pid.to_string(), "/T"])
+            .status();
+
+        status.is_ok_and(|s| s.success())
+    }
+
+    /// Re-templates `opencode.json` and asks the running sidecar to pick it up via the HTTP
+    /// reload endpoint, restarting it if that fails. opencode has no SIGHUP handler, so a
+    /// signal would only kill it.
+    pub fn reload_config<R: tauri::Runtime>(
+        app: &tauri::AppHandle<R>,
+    ) -> Result<ReloadOutcome, String> {
+        let config_dir = get_config_dir(app).map_err(|e| e.to_string())?;
+        copy_opencode_config(app, &config_dir).map_err(|e| e.to_string())?;
+        Ok(Self::apply_config(app))
+    }
+
+    /// Makes the sidecar pick up `opencode.json` as it is on disk, live if possible.
+    pub fn apply_config<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> ReloadOutcome {
+        let addr = Self::connect_addr(Self::bind_host(app), Self::active_port(app));
+        if Self::request_http_reload(&addr) {
+            println!("[Sidecar] Config reloaded via {}", RELOAD_ENDPOINT);
+            return ReloadOutcome {
+                method: ReloadMethod::Http,
+                live: true,
+            };
+        }
+
+        println!("[Sidecar] Live reload unsupported, restarting to apply config");
+        Self::restart_sidecar(app);
+        ReloadOutcome {
+            method: ReloadMethod::Restart,
+            live: false,
+        }
+    }
+
+    fn request_http_reload(addr: &str) -> bool {
+        let client = match reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+        {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+        let url = format!("http://{}{}", addr, RELOAD_ENDPOINT);
+        client
+            .post(url)
+            .send()
+            .map(|resp| resp.status().is_success())
+            .unwrap_or(false)
+    }
+}
+
+/// Parses a `GODOTY_PORT` value, or `None` when it isn't a number in `MIN_PORT..=65535`.
+fn parse_port(raw: &str) -> Option<u16> {
+    raw.trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|&port| port >= MIN_PORT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_ports() {
+        assert_eq!(parse_port("5000"), Some(5000));
+        assert_eq!(parse_port(" 1024 "), Some(1024));
+        assert_eq!(parse_port("65535"), Some(65535));
+    }
+
+    #[test]
+    fn falls_back_on_invalid_ports() {
+        for raw in ["", "abc", "80", "1023", "0", "65536", "-1", "4096x"] {
+            assert_eq!(parse_port(raw), None, "{:?}", raw);
+        }
+    }
+
+    #[test]
+    fn intentional_exits_are_not_crashes() {
+        let state = SidecarState::default();
+        state.intentional_shutdown.store(true, Ordering::SeqCst);
+        assert!(state.record_exit(Some(0), Duration::ZERO).is_none());
+        assert_eq!(state.crash_count.load(Ordering::SeqCst), 0);
+
+        state.intentional_shutdown.store(false, Ordering::SeqCst);
+        let crash = state.record_exit(Some(1), Duration::ZERO).unwrap();
+        assert_eq!((crash.attempt, crash.will_restart), (1, true));
+    }
 }
@@ -0,0 +1,125 @@
+use crate::config::ACTIVE_LOG_FILE;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// The active log is rotated once it would grow past this size.
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Appends captured sidecar output to `<logs_dir>/sidecar.log`. Shared by the stdout and
+/// stderr reader threads; the mutex keeps their lines whole and in order.
+pub struct SidecarLog {
+    inner: Mutex<LogFile>,
+}
+
+struct LogFile {
+    dir: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    /// Rotated files kept: `sidecar.1.log` (newest) to `sidecar.<N>.log`.
+    rotated_logs: usize,
+}
+
+impl SidecarLog {
+    /// Keeps `rotated_logs` old files besides the active one, at least one.
+    pub fn open(logs_dir: &Path, rotated_logs: usize) -> io::Result<Self> {
+        Self::with_limit(logs_dir, MAX_LOG_BYTES, rotated_logs)
+    }
+
+    fn with_limit(logs_dir: &Path, max_bytes: u64, rotated_logs: usize) -> io::Result<Self> {
+        fs::create_dir_all(logs_dir)?;
+        let (file, size) = open_active(logs_dir)?;
+        Ok(Self {
+            inner: Mutex::new(LogFile {
+                dir: logs_dir.to_path_buf(),
+                file,
+                size,
+                max_bytes,
+                rotated_logs: rotated_logs.max(1),
+            }),
+        })
+    }
+
+    pub fn write_line(&self, stream: &str, line: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        let entry = format!("[{:.3}] {}: {}\n", timestamp, stream, line);
+
+        let mut log = self.inner.lock().unwrap();
+        if log.size > 0 && log.size + entry.len() as u64 > log.max_bytes {
+            if let Err(e) = log.rotate() {
+                eprintln!("[Sidecar] Failed to rotate sidecar log: {}", e);
+            }
+        }
+        match log.file.write_all(entry.as_bytes()) {
+            Ok(()) => log.size += entry.len() as u64,
+            Err(e) => eprintln!("[Sidecar] Failed to write sidecar log: {}", e),
+        }
+    }
+}
+
+impl LogFile {
+    /// Shifts `sidecar.log` -> `sidecar.1.log` -> ... dropping the oldest, then reopens.
+    fn rotate(&mut self) -> io::Result<()> {
+        let _ = fs::remove_file(rotated_path(&self.dir, self.rotated_logs));
+        for n in (1..self.rotated_logs).rev() {
+            let from = rotated_path(&self.dir, n);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.dir, n + 1))?;
+            }
+        }
+        fs::rename(self.dir.join(ACTIVE_LOG_FILE), rotated_path(&self.dir, 1))?;
+        let (file, size) = open_active(&self.dir)?;
+        self.file = file;
+        self.size = size;
+        Ok(())
+    }
+}
+
+fn open_active(dir: &Path) -> io::Result<(File, u64)> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(ACTIVE_LOG_FILE))?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+fn rotated_path(dir: &Path, n: usize) -> PathBuf {
+    dir.join(format!("sidecar.{}.log", n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_and_keeps_two_old_logs() {
+        let dir = std::env::temp_dir().join(format!("godoty-log-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let log = SidecarLog::with_limit(&dir, 100, 2).unwrap();
+
+        // Each entry is over half the limit, so every write after the first rotates.
+        for i in 0..8 {
+            log.write_line("stdout", &format!("{:02} {}", i, "x".repeat(40)));
+        }
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap_or_default();
+        let (active, first, second) = (
+            read("sidecar.log"),
+            read("sidecar.1.log"),
+            read("sidecar.2.log"),
+        );
+        let third_exists = dir.join("sidecar.3.log").exists();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(active.contains("stdout: 07 "));
+        assert!(first.contains("stdout: 06 "));
+        assert!(second.contains("stdout: 05 "));
+        assert!(!third_exists);
+    }
+}
@@ -0,0 +1,467 @@
+use crate::updater::{Asset, Release};
+use crate::version;
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Everything the planner decides from. Gathered by the executor so planning does no I/O.
+pub struct PlanInput<'a> {
+    pub release: &'a Release,
+    /// Target triple the asset name must contain.
+    pub target: &'a str,
+    pub current_version: &'a str,
+    pub bin_path: &'a Path,
+    pub bin_exists: bool,
+    /// Staging directory the download is written to.
+    pub temp_dir: &'a Path,
+    pub allow_downgrade: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChecksumSource {
+    /// Nothing to verify against.
+    None,
+    /// Digest known up front, e.g. from a version manifest.
+    Pinned(String),
+    /// `sha256sum`-style file published next to the asset (`<asset>.sha256`) or for the
+    /// whole release (`checksums.txt`), fetched with it.
+    Companion { name: String, url: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    Zip,
+    /// `.tar.gz` / `.tgz`, as shipped for Linux and macOS.
+    TarGz,
+    /// The asset is the executable itself.
+    Binary,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateStep {
+    /// Downloads the asset and verifies it before anything is written to disk.
+    Download {
+        asset: String,
+        url: String,
+        checksum: ChecksumSource,
+    },
+    /// Writes the verified download into the staging dir, under the update lock.
+    Stage {
+        archive_path: PathBuf,
+    },
+    StopSidecar,
+    /// Moves the current binary aside so a failed install can be undone.
+    BackupCurrent {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Install {
+        archive_path: PathBuf,
+        format: ArchiveFormat,
+        bin_path: PathBuf,
+    },
+    MakeExecutable {
+        bin_path: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdatePlan {
+    pub version: String,
+    pub steps: Vec<UpdateStep>,
+}
+
+impl UpdatePlan {
+    /// What to restore when the step at `failed_at` fails: the backup, once it has been
+    /// taken. Failures before the backup leave the current binary untouched.
+    pub fn rollback_for(&self, failed_at: usize) -> Option<(&Path, &Path)> {
+        self.steps[..failed_at.min(self.steps.len())]
+            .iter()
+            .find_map(|step| match step {
+                UpdateStep::BackupCurrent { from, to } => Some((to.as_path(), from.as_path())),
+                _ => None,
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanError {
+    NoMatchingAsset { target: String },
+    Downgrade { current: String, release: String },
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::NoMatchingAsset { target } => {
+                write!(f, "No matching asset found for target: {}", target)
+            }
+            PlanError::Downgrade { current, release } => write!(
+                f,
+                "Refusing to downgrade sidecar from {} to {}",
+                current, release
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+pub fn plan(input: &PlanInput) -> Result<UpdatePlan, PlanError> {
+    let release = input.release;
+    let asset = select_asset(release, input.target).ok_or_else(|| PlanError::NoMatchingAsset {
+        target: input.target.to_string(),
+    })?;
+
+    if !input.allow_downgrade
+        && version::compare(&release.tag_name, input.current_version) == Some(Ordering::Less)
+    {
+        return Err(PlanError::Downgrade {
+            current: input.current_version.to_string(),
+            release: release.tag_name.clone(),
+        });
+    }
+
+    let archive_path = input.temp_dir.join(&asset.name);
+    let bin_path = input.bin_path.to_path_buf();
+    let mut steps = vec![
+        UpdateStep::Download {
+            asset: asset.name.clone(),
+            url: asset.browser_download_url.clone(),
+            checksum: checksum_source(release, asset),
+        },
+        UpdateStep::Stage {
+            archive_path: archive_path.clone(),
+        },
+        UpdateStep::StopSidecar,
+    ];
+    if input.bin_exists {
+        steps.push(UpdateStep::BackupCurrent {
+            from: bin_path.clone(),
+            to: backup_path(&bin_path),
+        });
+    }
+    steps.push(UpdateStep::Install {
+        archive_path,
+        format: archive_format(&asset.name),
+        bin_path: bin_path.clone(),
+    });
+    steps.push(UpdateStep::MakeExecutable { bin_path });
+
+    Ok(UpdatePlan {
+        version: release.tag_name.clone(),
+        steps,
+    })
+}
+
+/// Where the current binary is kept while an update is installed and verified.
+pub fn backup_path(bin_path: &Path) -> PathBuf {
+    bin_path.with_extension("old")
+}
+
+/// Every sidecar binary asset is named `opencode-...` or `opencode-cli-...`.
+const ASSET_PREFIX: &str = "opencode";
+/// Companion files that name the target triple but aren't the binary.
+const NON_BINARY_SUFFIXES: &[&str] = &[".sha256", ".sig", ".txt", "-debug"];
+
+/// Finds the sidecar binary for the target triple, skipping checksum, signature and debug
+/// files. An archive wins over a raw binary when a release ships both.
+pub fn select_asset<'a>(release: &'a Release, target: &str) -> Option<&'a Asset> {
+    release
+        .assets
+        .iter()
+        .filter(|a| is_binary_asset(&a.name, target))
+        .min_by_key(|a| archive_format(&a.name) == ArchiveFormat::Binary)
+}
+
+fn is_binary_asset(name: &str, target: &str) -> bool {
+    // Suffixes are matched ahead of any archive extension, as in `...-debug.tar.gz`.
+    let stem = [".zip", ".tar.gz", ".tgz"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name);
+    name.starts_with(ASSET_PREFIX)
+        && name.contains(target)
+        && !NON_BINARY_SUFFIXES.iter().any(|s| stem.ends_with(s))
+}
+
+/// Name of the aggregate checksum asset, used when an asset has no `.sha256` of its own.
+const AGGREGATE_CHECKSUMS: &str = "checksums.txt";
+
+/// A pinned digest wins; otherwise a listed `<asset>.sha256`, then `checksums.txt`, must
+/// be fetched and checked.
+fn checksum_source(release: &Release, asset: &Asset) -> ChecksumSource {
+    if let Some(digest) = &asset.sha256 {
+        return ChecksumSource::Pinned(digest.clone());
+    }
+    let companion = format!("{}.sha256", asset.name);
+    let find = |name: &str| release.assets.iter().find(|a| a.name == name);
+    match find(&companion).or_else(|| find(AGGREGATE_CHECKSUMS)) {
+        Some(c) => ChecksumSource::Companion {
+            name: c.name.clone(),
+            url: c.browser_download_url.clone(),
+        },
+        None => ChecksumSource::None,
+    }
+}
+
+fn archive_format(name: &str) -> ArchiveFormat {
+    if name.ends_with(".zip") {
+        ArchiveFormat::Zip
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        ArchiveFormat::TarGz
+    } else {
+        ArchiveFormat::Binary
+    }
+}
+
+/// Compares a computed hex digest against an expected one, ignoring case and whitespace.
+pub fn checksum_matches(actual: &str, expected: &str) -> bool {
+    actual.trim().eq_ignore_ascii_case(expected.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "x86_64-unknown-linux-gnu";
+
+    fn asset(name: &str, sha256: Option<&str>) -> Asset {
+        Asset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{}", name),
+            sha256: sha256.map(str::to_string),
+        }
+    }
+
+    fn release(tag: &str, assets: Vec<Asset>) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            assets,
+            body: None,
+            published_at: None,
+            prerelease: false,
+            draft: false,
+        }
+    }
+
+    fn input<'a>(release: &'a Release, current: &'a str, bin_exists: bool) -> PlanInput<'a> {
+        PlanInput {
+            release,
+            target: TARGET,
+            current_version: current,
+            bin_path: Path::new("/cfg/bin/opencode-cli"),
+            bin_exists,
+            temp_dir: Path::new("/tmp/godoty-update"),
+            allow_downgrade: false,
+        }
+    }
+
+    #[test]
+    fn plans_backup_install_and_chmod_in_order() {
+        let release = release(
+            "v1.2.0",
+            vec![asset("opencode-x86_64-unknown-linux-gnu.zip", None)],
+        );
+        let plan = plan(&input(&release, "1.1.0", true)).unwrap();
+
+        assert_eq!(plan.version, "v1.2.0");
+        assert_eq!(
+            plan.steps,
+            vec![
+                UpdateStep::Download {
+                    asset: "opencode-x86_64-unknown-linux-gnu.zip".to_string(),
+                    url: "https://example.com/opencode-x86_64-unknown-linux-gnu.zip".to_string(),
+                    checksum: ChecksumSource::None,
+                },
+                UpdateStep::Stage {
+                    archive_path: PathBuf::from(
+                        "/tmp/godoty-update/opencode-x86_64-unknown-linux-gnu.zip"
+                    ),
+                },
+                UpdateStep::StopSidecar,
+                UpdateStep::BackupCurrent {
+                    from: PathBuf::from("/cfg/bin/opencode-cli"),
+                    to: PathBuf::from("/cfg/bin/opencode-cli.old"),
+                },
+                UpdateStep::Install {
+                    archive_path: PathBuf::from(
+                        "/tmp/godoty-update/opencode-x86_64-unknown-linux-gnu.zip"
+                    ),
+                    format: ArchiveFormat::Zip,
+                    bin_path: PathBuf::from("/cfg/bin/opencode-cli"),
+                },
+                UpdateStep::MakeExecutable {
+                    bin_path: PathBuf::from("/cfg/bin/opencode-cli"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fresh_install_has_no_backup_or_rollback() {
+        let release = release(
+            "v1.2.0",
+            vec![asset("opencode-x86_64-unknown-linux-gnu", None)],
+        );
+        let plan = plan(&input(&release, "0.0.0", false)).unwrap();
+
+        assert!(!plan
+            .steps
+            .iter()
+            .any(|s| matches!(s, UpdateStep::BackupCurrent { .. })));
+        assert!(plan.steps.iter().any(|s| matches!(
+            s,
+            UpdateStep::Install {
+                format: ArchiveFormat::Binary,
+                ..
+            }
+        )));
+        assert_eq!(plan.rollback_for(plan.steps.len()), None);
+    }
+
+    #[test]
+    fn picks_checksum_source() {
+        let pinned = release(
+            "v1.2.0",
+            vec![asset("opencode-x86_64-unknown-linux-gnu.zip", Some("abc"))],
+        );
+        let companion = release(
+            "v1.2.0",
+            vec![
+                asset("opencode-x86_64-unknown-linux-gnu.zip", None),
+                asset("opencode-x86_64-unknown-linux-gnu.zip.sha256", None),
+            ],
+        );
+        let checksum =
+            |release: &Release| match &plan(&input(release, "1.0.0", true)).unwrap().steps[0] {
+                UpdateStep::Download { checksum, .. } => checksum.clone(),
+                step => panic!("unexpected first step {:?}", step),
+            };
+
+        assert_eq!(checksum(&pinned), ChecksumSource::Pinned("abc".to_string()));
+        assert_eq!(
+            checksum(&companion),
+            ChecksumSource::Companion {
+                name: "opencode-x86_64-unknown-linux-gnu.zip.sha256".to_string(),
+                url: "https://example.com/opencode-x86_64-unknown-linux-gnu.zip.sha256".to_string(),
+            }
+        );
+        let aggregate = release(
+            "v1.2.0",
+            vec![
+                asset("opencode-x86_64-unknown-linux-gnu.zip", None),
+                asset("checksums.txt", None),
+            ],
+        );
+        assert_eq!(
+            checksum(&aggregate),
+            ChecksumSource::Companion {
+                name: "checksums.txt".to_string(),
+                url: "https://example.com/checksums.txt".to_string(),
+            }
+        );
+        assert!(checksum_matches("ABCDEF", " abcdef\n"));
+        assert!(!checksum_matches("abcdef", "abcdee"));
+    }
+
+    #[test]
+    fn rejects_missing_asset_and_downgrade() {
+        let release = release(
+            "v1.0.0",
+            vec![asset("opencode-aarch64-apple-darwin.zip", None)],
+        );
+        assert_eq!(
+            plan(&input(&release, "0.9.0", true)),
+            Err(PlanError::NoMatchingAsset {
+                target: TARGET.to_string()
+            })
+        );
+
+        let release = self::release(
+            "v1.0.0",
+            vec![asset("opencode-x86_64-unknown-linux-gnu.zip", None)],
+        );
+        assert_eq!(
+            plan(&input(&release, "1.2.0", true)),
+            Err(PlanError::Downgrade {
+                current: "1.2.0".to_string(),
+                release: "v1.0.0".to_string()
+            })
+        );
+
+        let mut allowed = input(&release, "1.2.0", true);
+        allowed.allow_downgrade = true;
+        assert!(plan(&allowed).is_ok());
+    }
+
+    #[test]
+    fn rollback_triggers_only_after_backup() {
+        let release = release(
+            "v1.2.0",
+            vec![asset("opencode-x86_64-unknown-linux-gnu.zip", None)],
+        );
+        let plan = plan(&input(&release, "1.1.0", true)).unwrap();
+        let backup = plan
+            .steps
+            .iter()
+            .position(|s| matches!(s, UpdateStep::BackupCurrent { .. }))
+            .unwrap();
+
+        assert_eq!(plan.rollback_for(0), None);
+        assert_eq!(plan.rollback_for(backup), None);
+        assert_eq!(
+            plan.rollback_for(backup + 1),
+            Some((
+                Path::new("/cfg/bin/opencode-cli.old"),
+                Path::new("/cfg/bin/opencode-cli")
+            ))
+        );
+    }
+
+    #[test]
+    fn selects_binary_asset_over_companion_files() {
+        let release = release(
+            "v1.2.0",
+            vec![
+                asset("opencode-x86_64-unknown-linux-gnu.sha256", None),
+                asset("opencode-x86_64-unknown-linux-gnu-debug.tar.gz", None),
+                asset("symbols-x86_64-unknown-linux-gnu.zip", None),
+                asset("opencode-x86_64-unknown-linux-gnu.tar.gz.sig", None),
+                asset("opencode-x86_64-unknown-linux-gnu.txt", None),
+                asset("opencode-cli-x86_64-unknown-linux-gnu.tar.gz", None),
+            ],
+        );
+        assert_eq!(
+            select_asset(&release, TARGET).map(|a| a.name.as_str()),
+            Some("opencode-cli-x86_64-unknown-linux-gnu.tar.gz")
+        );
+    }
+
+    #[test]
+    fn prefers_archive_over_raw_binary() {
+        let release = release(
+            "v1.2.0",
+            vec![
+                asset("opencode-x86_64-unknown-linux-gnu", None),
+                asset("opencode-x86_64-unknown-linux-gnu.zip", None),
+            ],
+        );
+        assert_eq!(
+            select_asset(&release, TARGET).map(|a| a.name.as_str()),
+            Some("opencode-x86_64-unknown-linux-gnu.zip")
+        );
+    }
+
+    #[test]
+    fn finds_no_asset_when_only_companions_match() {
+        let release = release(
+            "v1.2.0",
+            vec![
+                asset("opencode-x86_64-unknown-linux-gnu.zip.sha256", None),
+                asset("opencode-aarch64-apple-darwin.zip", None),
+            ],
+        );
+        assert!(select_asset(&release, TARGET).is_none());
+    }
+}
@@ -1,14 +1,143 @@
-use reqwest::blocking::Client;
+use crate::process;
+use crate::sidecar::SidecarManager;
+use crate::update_plan::{
+    self, ArchiveFormat, ChecksumSource, PlanError, PlanInput, UpdatePlan, UpdateStep,
+};
+use crate::version;
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{AUTHORIZATION, LOCATION, RETRY_AFTER};
+use reqwest::redirect::Policy;
+use reqwest::{StatusCode, Url};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager, Runtime};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 use zip::ZipArchive;
 
+/// Prefix of the temp directory used to stage downloaded updates.
+pub const UPDATE_TEMP_PREFIX: &str = "godoty-update";
+/// Lockfile held inside the staging directory while an update is running.
+pub const UPDATE_LOCK_FILE: &str = ".update.lock";
+/// Maximum number of redirects followed for a single release request.
+const MAX_REDIRECTS: usize = 10;
+/// Attempts made for each release lookup or download before giving up.
+const GET_ATTEMPTS: u32 = 3;
+/// Wait before the first retry, doubled for each one after.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Longest `Retry-After` honoured, so a rate limit can't stall an update indefinitely.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// GitHub REST API base used for release lookups, unless `GODOTY_UPDATE_API_BASE` is set.
+const GITHUB_API_BASE: &str = "https://api.github.com";
+/// Repository the sidecar releases are published to, unless `GODOTY_UPDATE_REPO` is set.
+const RELEASE_REPO: &str = "anomalyco/opencode";
+/// Sidecar versions whose API the config templating and commands are written against.
+pub const SIDECAR_COMPAT_RANGE: &str = ">=1.0.0, <2.0.0";
+/// Timeout for the connectivity probe, kept short so diagnostics stay responsive.
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where releases are looked up: an `owner/name` repo on a GitHub (Enterprise) API.
+#[derive(Debug, Clone, PartialEq)]
+struct ReleaseSource {
+    api_base: String,
+    repo: String,
+}
+
+impl ReleaseSource {
+    fn from_env() -> Self {
+        Self::parse(
+            std::env::var("GODOTY_UPDATE_REPO").ok().as_deref(),
+            std::env::var("GODOTY_UPDATE_API_BASE").ok().as_deref(),
+        )
+    }
+
+    /// Malformed values are logged and replaced by the defaults.
+    fn parse(repo: Option<&str>, api_base: Option<&str>) -> Self {
+        let repo = match repo.map(str::trim).filter(|r| !r.is_empty()) {
+            Some(repo) if is_owner_and_name(repo) => repo.to_string(),
+            Some(repo) => {
+                eprintln!(
+                    "[Updater] Ignoring GODOTY_UPDATE_REPO '{}': expected owner/name",
+                    repo
+                );
+                RELEASE_REPO.to_string()
+            }
+            None => RELEASE_REPO.to_string(),
+        };
+        let api_base = match api_base.map(str::trim).filter(|b| !b.is_empty()) {
+            Some(base) if Url::parse(base).is_ok() => base.trim_end_matches('/').to_string(),
+            Some(base) => {
+                eprintln!(
+                    "[Updater] Ignoring GODOTY_UPDATE_API_BASE '{}': not a URL",
+                    base
+                );
+                GITHUB_API_BASE.to_string()
+            }
+            None => GITHUB_API_BASE.to_string(),
+        };
+        Self { api_base, repo }
+    }
+
+    fn releases_url(&self) -> String {
+        format!("{}/repos/{}/releases", self.api_base, self.repo)
+    }
+
+    fn latest_release_url(&self) -> String {
+        format!("{}/latest", self.releases_url())
+    }
+
+    /// A GitHub Enterprise API host is trusted with the token like github.com.
+    fn is_api_host(&self, url: &str) -> bool {
+        match (Url::parse(url), Url::parse(&self.api_base)) {
+            (Ok(url), Ok(base)) => is_same_host(&url, &base),
+            _ => false,
+        }
+    }
+}
+
+fn is_owner_and_name(repo: &str) -> bool {
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    repo.split_once('/')
+        .is_some_and(|(owner, name)| valid(owner) && valid(name))
+}
+
+/// Marks the staging directory as in use; the lockfile is removed on drop,
+/// including when the update bails out early with an error.
+struct UpdateLock {
+    path: PathBuf,
+}
+
+impl UpdateLock {
+    fn acquire(dir: &Path) -> std::io::Result<Self> {
+        let path = dir.join(UPDATE_LOCK_FILE);
+        fs::write(&path, std::process::id().to_string())?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for UpdateLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Asset {
     pub name: String,
     pub browser_download_url: String,
+    /// Expected hex digest; only known for assets from a version manifest.
+    #[serde(default)]
+    pub sha256: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -17,52 +146,445 @@ pub struct Release {
     pub assets: Vec<Asset>,
     pub body: Option<String>,
     pub published_at: Option<String>,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// Self-hosted pointer to the approved sidecar version, read from
+/// `GODOTY_VERSION_MANIFEST_URL` in place of GitHub releases.
+#[derive(Debug, Deserialize)]
+struct VersionManifest {
+    version: String,
+    assets: Vec<ManifestAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestAsset {
+    name: String,
+    url: String,
+    sha256: String,
+}
+
+impl From<VersionManifest> for Release {
+    fn from(manifest: VersionManifest) -> Self {
+        Release {
+            tag_name: manifest.version,
+            assets: manifest
+                .assets
+                .into_iter()
+                .map(|a| Asset {
+                    name: a.name,
+                    browser_download_url: a.url,
+                    sha256: Some(a.sha256),
+                })
+                .collect(),
+            body: None,
+            published_at: None,
+            prerelease: false,
+            draft: false,
+        }
+    }
+}
+
+/// Most recent release fetched by `get_latest_release`, kept for diagnostics.
+#[derive(Default)]
+pub struct ReleaseCache {
+    pub release: std::sync::Mutex<Option<Release>>,
+}
+
+/// Ensures only one sidecar update runs at a time.
+#[derive(Default)]
+pub struct UpdateGuard {
+    in_progress: Arc<AtomicBool>,
+}
+
+/// Held for the duration of an update; clears the guard when dropped, including on errors.
+pub struct UpdateInProgress {
+    flag: Arc<AtomicBool>,
+}
+
+impl UpdateGuard {
+    pub fn try_begin(&self) -> Result<UpdateInProgress, UpdaterError> {
+        self.in_progress
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .map_err(|_| UpdaterError::InProgress)?;
+        Ok(UpdateInProgress {
+            flag: self.in_progress.clone(),
+        })
+    }
+}
+
+impl Drop for UpdateInProgress {
+    fn drop(&mut self) {
+        self.flag.store(false, Ordering::SeqCst);
+    }
+}
+
+/// What the updater looks for in a release versus what the cached release provides.
+#[derive(Debug, Serialize)]
+pub struct ExpectedAsset {
+    pub target: String,
+    /// `None` until a release has been fetched this session.
+    pub release_tag: Option<String>,
+    pub available_assets: Vec<String>,
+    pub matched_asset: Option<String>,
+}
+
+/// Payload of the `sidecar-update-fallback` event, sent when a failed update left no
+/// sidecar binary and the bundled one was reinstalled in its place.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleFallback {
+    pub error: String,
+    pub restored: bool,
+}
+
+/// Minimum gap between `sidecar-update-progress` events, so a fast local download
+/// doesn't flood the frontend. The final chunk is always reported.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Payload of the `sidecar-update-progress` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateProgress {
+    pub downloaded: u64,
+    /// From `Content-Length`; `None` when the server doesn't send it.
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+}
+
+/// Payload of the `sidecar-update-complete` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateComplete {
+    pub version: String,
+}
+
+/// Payload of the `sidecar-update-error` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateFailed {
+    pub kind: &'static str,
+    pub error: String,
+}
+
+impl From<&UpdaterError> for UpdateFailed {
+    fn from(error: &UpdaterError) -> Self {
+        Self {
+            kind: error.kind(),
+            error: error.to_string(),
+        }
+    }
+}
+
+/// Why an updater operation failed. Serialized as `{ kind, message, status }` so the
+/// frontend can branch on `kind` instead of parsing `message`.
+#[derive(Debug)]
+pub enum UpdaterError {
+    /// No response at all: DNS, connection, TLS or timeout.
+    Network(String),
+    /// The server answered with a non-success status.
+    Http {
+        status: u16,
+        context: String,
+    },
+    /// A response that can't be used, such as malformed release JSON.
+    InvalidResponse(String),
+    /// The release source has no release to offer.
+    NoRelease(String),
+    NoAsset {
+        target: String,
+    },
+    Downgrade {
+        current: String,
+        release: String,
+    },
+    ChecksumMismatch {
+        asset: String,
+        expected: String,
+        actual: String,
+    },
+    /// The archive is corrupt or holds no sidecar binary.
+    Extract(String),
+    /// The current binary can't be moved aside, usually because it is still running.
+    BinaryLocked(String),
+    /// The installed binary failed to run or never became healthy.
+    Verification(String),
+    /// No bundled sidecar ships with this build.
+    NoBundle(String),
+    InProgress,
+    Io(std::io::Error),
+    Other(String),
+    /// `error`, followed by what was done about it. Keeps the kind of `error`.
+    WithContext {
+        error: Box<UpdaterError>,
+        context: String,
+    },
+}
+
+impl UpdaterError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UpdaterError::Network(_) => "network",
+            UpdaterError::Http { .. } => "http",
+            UpdaterError::InvalidResponse(_) => "invalid_response",
+            UpdaterError::NoRelease(_) => "no_release",
+            UpdaterError::NoAsset { .. } => "no_asset",
+            UpdaterError::Downgrade { .. } => "downgrade",
+            UpdaterError::ChecksumMismatch { .. } => "checksum_mismatch",
+            UpdaterError::Extract(_) => "extract",
+            UpdaterError::BinaryLocked(_) => "binary_locked",
+            UpdaterError::Verification(_) => "verification",
+            UpdaterError::NoBundle(_) => "no_bundle",
+            UpdaterError::InProgress => "in_progress",
+            UpdaterError::Io(_) => "io",
+            UpdaterError::Other(_) => "other",
+            UpdaterError::WithContext { error, .. } => error.kind(),
+        }
+    }
+
+    /// The HTTP status, for `Http` errors.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            UpdaterError::Http { status, .. } => Some(*status),
+            UpdaterError::WithContext { error, .. } => error.status(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for UpdaterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpdaterError::Network(message)
+            | UpdaterError::InvalidResponse(message)
+            | UpdaterError::NoRelease(message)
+            | UpdaterError::Extract(message)
+            | UpdaterError::BinaryLocked(message)
+            | UpdaterError::Verification(message)
+            | UpdaterError::NoBundle(message)
+            | UpdaterError::Other(message) => write!(f, "{}", message),
+            UpdaterError::Http { status, context } => write!(f, "{}: HTTP {}", context, status),
+            UpdaterError::NoAsset { target } => {
+                write!(f, "No matching asset found for target: {}", target)
+            }
+            UpdaterError::Downgrade { current, release } => write!(
+                f,
+                "Refusing to downgrade sidecar from {} to {}",
+                current, release
+            ),
+            UpdaterError::ChecksumMismatch {
+                asset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch for {}: expected {}, got {}",
+                asset, expected, actual
+            ),
+            UpdaterError::InProgress => write!(f, "update already in progress"),
+            UpdaterError::Io(e) => write!(f, "{}", e),
+            UpdaterError::WithContext { error, context } => write!(f, "{}; {}", error, context),
+        }
+    }
+}
+
+impl std::error::Error for UpdaterError {}
+
+impl Serialize for UpdaterError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("UpdaterError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("status", &self.status())?;
+        state.end()
+    }
+}
+
+impl From<std::io::Error> for UpdaterError {
+    fn from(e: std::io::Error) -> Self {
+        UpdaterError::Io(e)
+    }
+}
+
+impl From<reqwest::Error> for UpdaterError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_decode() {
+            UpdaterError::InvalidResponse(e.to_string())
+        } else {
+            UpdaterError::Network(e.to_string())
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for UpdaterError {
+    fn from(e: zip::result::ZipError) -> Self {
+        UpdaterError::Extract(e.to_string())
+    }
+}
+
+impl From<PlanError> for UpdaterError {
+    fn from(e: PlanError) -> Self {
+        match e {
+            PlanError::NoMatchingAsset { target } => UpdaterError::NoAsset { target },
+            PlanError::Downgrade { current, release } => {
+                UpdaterError::Downgrade { current, release }
+            }
+        }
+    }
+}
+
+/// Failing to resolve the config directory.
+impl From<tauri::Error> for UpdaterError {
+    fn from(e: tauri::Error) -> Self {
+        UpdaterError::Other(e.to_string())
+    }
+}
+
+/// Outcome of probing the update host, independent of any release lookup.
+#[derive(Debug, Serialize)]
+pub struct ConnectivityReport {
+    pub reachable: bool,
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Result of `fix_permissions`; modes are `None` on platforms without Unix permissions.
+#[derive(Debug, Serialize)]
+pub struct PermissionFix {
+    pub path: String,
+    pub before_mode: Option<u32>,
+    pub after_mode: Option<u32>,
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionCompatibility {
+    pub compatible: bool,
+    pub sidecar_version: String,
+    pub required_range: String,
+    pub message: String,
+}
+
+impl VersionCompatibility {
+    pub fn check(sidecar_version: &str) -> Self {
+        let required = semver::VersionReq::parse(SIDECAR_COMPAT_RANGE)
+            .expect("SIDECAR_COMPAT_RANGE is a valid semver requirement");
+        let trimmed = sidecar_version.trim_start_matches('v');
+
+        let (compatible, message) = match semver::Version::parse(trimmed) {
+            Ok(_) if trimmed == "0.0.0" => (false, "Sidecar is not installed".to_string()),
+            Ok(version) if required.matches(&version) => {
+                (true, format!("Sidecar v{} is compatible", version))
+            }
+            Ok(version) => (
+                false,
+                format!(
+                    "Sidecar v{} is outside the supported range {}; some features may not work",
+                    version, SIDECAR_COMPAT_RANGE
+                ),
+            ),
+            Err(_) => (
+                false,
+                format!(
+                    "Could not determine sidecar version from '{}'; expected {}",
+                    sidecar_version, SIDECAR_COMPAT_RANGE
+                ),
+            ),
+        };
+
+        Self {
+            compatible,
+            sidecar_version: sidecar_version.to_string(),
+            required_range: SIDECAR_COMPAT_RANGE.to_string(),
+            message,
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct Updater<R: Runtime> {
     client: Client,
     app_handle: AppHandle<R>,
+    github_token: Option<String>,
+    source: ReleaseSource,
+    /// From `GODOTY_ALLOW_PRERELEASE`; pre-releases are skipped unless it is set.
+    allow_prerelease: bool,
 }
 
 impl<R: Runtime> Updater<R> {
     pub fn new(app: &AppHandle<R>) -> Self {
+        let github_token = std::env::var("GODOTY_GITHUB_TOKEN")
+            .or_else(|_| std::env::var("GITHUB_TOKEN"))
+            .ok()
+            .filter(|t| !t.trim().is_empty());
+
         Self {
             client: Client::builder()
                 .user_agent("godoty-updater")
                 .timeout(std::time::Duration::from_secs(30))
+                // Redirects are followed by `get_following_redirects` so auth can be stripped.
+                .redirect(Policy::none())
                 .build()
                 .unwrap(),
             app_handle: app.clone(),
+            github_token,
+            source: ReleaseSource::from_env(),
+            allow_prerelease: std::env::var("GODOTY_ALLOW_PRERELEASE").is_ok_and(|v| {
+                matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes")
+            }),
         }
     }
 
-    pub fn get_sidecar_path(&self) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
-        let path = crate::config::get_sidecar_path(&self.app_handle)
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    /// The GitHub token is only ever offered to GitHub, never to a self-hosted mirror.
+    fn get(&self, url: &str) -> Result<Response, UpdaterError> {
+        let token = self
+            .github_token
+            .as_deref()
+            .filter(|_| is_github_url(url) || self.source.is_api_host(url));
+        retry_get(url, GET_ATTEMPTS, RETRY_BASE_DELAY, || {
+            get_following_redirects(&self.client, url, token)
+        })
+    }
+
+    fn manifest_url() -> Option<String> {
+        std::env::var("GODOTY_VERSION_MANIFEST_URL")
+            .ok()
+            .filter(|u| !u.trim().is_empty())
+    }
+
+    fn get_manifest_release(&self, url: &str) -> Result<Release, UpdaterError> {
+        let resp = self.get(url)?;
+        if !resp.status().is_success() {
+            return Err(UpdaterError::Http {
+                status: resp.status().as_u16(),
+                context: "Failed to fetch version manifest".to_string(),
+            });
+        }
+        let manifest: VersionManifest = resp.json().map_err(|e| {
+            UpdaterError::InvalidResponse(format!("Invalid version manifest at {}: {}", url, e))
+        })?;
+        let release = Release::from(manifest);
+        self.cache_release(&release);
+        Ok(release)
+    }
+
+    pub fn get_sidecar_path(&self) -> Result<PathBuf, UpdaterError> {
+        let path = crate::config::get_sidecar_path(&self.app_handle)?;
         if let Some(parent) = path.parent() {
             if !parent.exists() {
-                fs::create_dir_all(parent)
-                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+                fs::create_dir_all(parent)?;
             }
         }
         Ok(path)
     }
 
-    pub fn ensure_installed(&self) -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync>> {
+    pub fn ensure_installed(&self) -> Result<PathBuf, UpdaterError> {
         let install_path = self.get_sidecar_path()?;
 
         if install_path.exists() {
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                if let Ok(metadata) = fs::metadata(&install_path) {
-                    let mut perms = metadata.permissions();
-                    if perms.mode() & 0o111 == 0 {
-                        perms.set_mode(0o755);
-                        let _ = fs::set_permissions(&install_path, perms);
-                    }
-                }
+            if let Err(e) = fix_permissions(&install_path) {
+                eprintln!("[Updater] Failed to fix sidecar permissions: {}", e);
             }
             return Ok(install_path);
         }
@@ -72,31 +594,39 @@ impl<R: Runtime> Updater<R> {
             install_path
         );
 
-        let bundled_path = self
-            .find_bundled_binary()
-            .map_err(|e| Box::<dyn std::error::Error + Send + Sync>::from(e))?;
-        println!("[Updater] Found bundled binary at {:?}", bundled_path);
-
-        if let Some(parent) = install_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-        }
+        let bundled_path = self.find_bundled_binary().map_err(UpdaterError::NoBundle)?;
+        install_bundled(&bundled_path, &install_path)?;
+        Ok(install_path)
+    }
 
-        fs::copy(&bundled_path, &install_path)
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-        println!("[Updater] Copied to {:?}", install_path);
+    /// Replaces the installed sidecar, and any update backup, with the bundled binary.
+    /// The bundle is located first, so nothing is deleted when there is none. Returns the
+    /// reinstalled version. The sidecar must already be stopped.
+    pub fn force_reinstall(&self) -> Result<String, UpdaterError> {
+        let bundled_path = self.find_bundled_binary().map_err(UpdaterError::NoBundle)?;
+        let install_path = self.get_sidecar_path()?;
 
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            fs::set_permissions(&install_path, fs::Permissions::from_mode(0o755))
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        for path in [
+            update_plan::backup_path(&install_path),
+            install_path.clone(),
+        ] {
+            match fs::remove_file(&path) {
+                Ok(()) => println!("[Updater] Removed {:?}", path),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(UpdaterError::BinaryLocked(format!(
+                        "Could not remove {:?}: {}",
+                        path, e
+                    )))
+                }
+            }
         }
 
-        Ok(install_path)
+        install_bundled(&bundled_path, &install_path)?;
+        binary_version(&install_path)
     }
 
-    fn find_bundled_binary(&self) -> Result<PathBuf, String> {
+    pub fn find_bundled_binary(&self) -> Result<PathBuf, String> {
         // In development, it might be in `src-tauri/bin` or just `bin` relative to CWD
         // In production, it's in the resource directory.
 
@@ -144,197 +674,1186 @@ impl<R: Runtime> Updater<R> {
         Err("Could not find bundled opencode-cli binary in resources or local bin folder".into())
     }
 
-    pub fn get_latest_release(&self) -> Result<Release, Box<dyn std::error::Error + Send + Sync>> {
-        let url = "https://api.github.com/repos/anomalyco/opencode/releases/latest";
-        let resp = self
-            .client
-            .get(url)
-            .send()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    pub fn get_latest_release(&self) -> Result<Release, UpdaterError> {
+        if let Some(manifest_url) = Self::manifest_url() {
+            return self.get_manifest_release(&manifest_url);
+        }
+
+        // GitHub never marks a pre-release as latest, so they are only found in the list.
+        if self.allow_prerelease {
+            return self.get_newest_listed_release();
+        }
+
+        let url = self.source.latest_release_url();
+        let resp = self.get(&url)?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            println!("[Updater] No release marked latest, falling back to the release list");
+            return self.get_newest_listed_release();
+        }
         if !resp.status().is_success() {
-            return Err(Box::<dyn std::error::Error + Send + Sync>::from(format!(
-                "Failed to fetch release: {}",
-                resp.status()
-            )));
+            return Err(UpdaterError::Http {
+                status: resp.status().as_u16(),
+                context: "Failed to fetch release".to_string(),
+            });
         }
-        let release: Release = resp
-            .json()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let release: Release = resp.json()?;
+        if is_prerelease(&release) {
+            println!(
+                "[Updater] Latest release {} is a pre-release, falling back to the release list",
+                release.tag_name
+            );
+            return self.get_newest_listed_release();
+        }
+        self.cache_release(&release);
         Ok(release)
     }
 
-    pub fn get_current_version(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    fn cache_release(&self, release: &Release) {
+        if let Some(cache) = self.app_handle.try_state::<ReleaseCache>() {
+            *cache.release.lock().unwrap() = Some(release.clone());
+        }
+    }
+
+    /// Compares the asset name for this platform against the cached release, if any.
+    pub fn expected_asset(&self) -> ExpectedAsset {
+        let target = self.get_target_asset_name();
+        let cached = self
+            .app_handle
+            .try_state::<ReleaseCache>()
+            .and_then(|cache| cache.release.lock().unwrap().clone());
+
+        match cached {
+            Some(release) => ExpectedAsset {
+                matched_asset: update_plan::select_asset(&release, &target).map(|a| a.name.clone()),
+                release_tag: Some(release.tag_name),
+                available_assets: release.assets.into_iter().map(|a| a.name).collect(),
+                target,
+            },
+            None => ExpectedAsset {
+                target,
+                release_tag: None,
+                available_assets: Vec::new(),
+                matched_asset: None,
+            },
+        }
+    }
+
+    fn download(&self, url: &str) -> Result<Vec<u8>, UpdaterError> {
+        self.download_with_progress(url, |_, _| {})
+    }
+
+    /// Reads the body in chunks, calling `on_progress` with bytes read so far and the
+    /// `Content-Length`, if any.
+    fn download_with_progress(
+        &self,
+        url: &str,
+        mut on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<Vec<u8>, UpdaterError> {
+        let mut resp = self.get(url)?;
+        if !resp.status().is_success() {
+            return Err(UpdaterError::Http {
+                status: resp.status().as_u16(),
+                context: format!("Failed to download {}", url),
+            });
+        }
+        let total = resp.content_length();
+        let mut bytes = Vec::new();
+        let mut chunk = [0u8; 64 * 1024];
+        loop {
+            let read = resp
+                .read(&mut chunk)
+                .map_err(|e| UpdaterError::Network(format!("Failed to download {}: {}", url, e)))?;
+            if read == 0 {
+                break;
+            }
+            bytes.extend_from_slice(&chunk[..read]);
+            on_progress(bytes.len() as u64, total);
+        }
+        Ok(bytes)
+    }
+
+    /// Emits `sidecar-update-progress`, throttled to `PROGRESS_INTERVAL` except for the
+    /// final chunk.
+    fn emit_progress(&self, last: &mut Option<Instant>, downloaded: u64, total: Option<u64>) {
+        let finished = total == Some(downloaded);
+        if !finished && last.is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+            return;
+        }
+        *last = Some(Instant::now());
+        let percent = total
+            .filter(|&t| t > 0)
+            .map(|t| downloaded as f64 * 100.0 / t as f64);
+        let _ = self.app_handle.emit(
+            "sidecar-update-progress",
+            UpdateProgress {
+                downloaded,
+                total,
+                percent,
+            },
+        );
+    }
+
+    /// Picks the newest published release from `/releases`, for repos that never mark one
+    /// as latest or when pre-releases are allowed.
+    fn get_newest_listed_release(&self) -> Result<Release, UpdaterError> {
+        let url = self.source.releases_url();
+        let resp = self.get(&url)?;
+        if !resp.status().is_success() {
+            return Err(UpdaterError::Http {
+                status: resp.status().as_u16(),
+                context: "Failed to fetch release list".to_string(),
+            });
+        }
+        let releases: Vec<Release> = resp.json()?;
+
+        let release = select_newest(releases, self.allow_prerelease).ok_or_else(|| {
+            UpdaterError::NoRelease(if self.allow_prerelease {
+                "No published releases found".to_string()
+            } else {
+                "No published stable releases found (set GODOTY_ALLOW_PRERELEASE to include pre-releases)".to_string()
+            })
+        })?;
+        self.cache_release(&release);
+        Ok(release)
+    }
+
+    /// Sends a HEAD request to the API base. Any HTTP response, even an error status,
+    /// means the host is reachable; only transport failures count as unreachable.
+    pub fn check_connectivity(&self) -> ConnectivityReport {
+        let start = Instant::now();
+        let result = self
+            .client
+            .head(&self.source.api_base)
+            .timeout(CONNECTIVITY_TIMEOUT)
+            .send();
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(resp) => ConnectivityReport {
+                reachable: true,
+                status: Some(resp.status().as_u16()),
+                latency_ms,
+                error: None,
+            },
+            Err(e) => ConnectivityReport {
+                reachable: false,
+                status: None,
+                latency_ms,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    pub fn get_current_version(&self) -> Result<String, UpdaterError> {
         let bin_path = self.get_sidecar_path()?;
 
         if !bin_path.exists() {
             return Ok("0.0.0".to_string());
         }
 
-        // Run `opencode-cli --version`
-        // Expected output: "opencode-cli 0.1.0" or just "0.1.0"
-        let output = std::process::Command::new(&bin_path)
+        binary_version(&bin_path)
+    }
+
+    pub fn get_compatibility(&self) -> Result<VersionCompatibility, UpdaterError> {
+        Ok(VersionCompatibility::check(&self.get_current_version()?))
+    }
+
+    pub fn get_target_asset_name(&self) -> String {
+        use std::env::consts::{ARCH, OS};
+        match target_triple(OS, ARCH) {
+            Some(triple) => triple.to_string(),
+            None => {
+                // Matches no asset, so the update fails with a clear "no matching asset".
+                eprintln!("[Updater] No release target for {} on {}", ARCH, OS);
+                format!("{}-{}", ARCH, OS)
+            }
+        }
+    }
+
+    /// Checks that the installed binary runs at all, before waiting on its health check.
+    pub fn smoke_test(&self) -> Result<(), UpdaterError> {
+        let bin_path = self.get_sidecar_path()?;
+        let status = process::command(&bin_path)
             .arg("--version")
-            .output()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            .status()
+            .map_err(|e| {
+                UpdaterError::Verification(format!("Failed to run {:?} --version: {}", bin_path, e))
+            })?;
+        if !status.success() {
+            return Err(UpdaterError::Verification(format!(
+                "{:?} --version exited with {}",
+                bin_path, status
+            )));
+        }
+        Ok(())
+    }
 
-        if !output.status.success() {
-            return Ok("0.0.0".to_string());
+    /// Puts the binary backed up by the last update back in place. Returns whether a
+    /// backup was restored.
+    pub fn restore_backup(&self) -> Result<bool, UpdaterError> {
+        let bin_path = self.get_sidecar_path()?;
+        let backup = update_plan::backup_path(&bin_path);
+        if !backup.exists() {
+            return Ok(false);
+        }
+        println!("[Updater] Restoring {:?}", backup);
+        if bin_path.exists() {
+            fs::remove_file(&bin_path)?;
         }
+        fs::rename(&backup, &bin_path)?;
+        Ok(true)
+    }
 
-        let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        // Parse "opencode 0.1.2" -> "0.1.2"
-        if let Some(last) = version_str.split_whitespace().last() {
-            // simplistic check if it looks like a version
-            if last.contains('.') {
-                return Ok(last.to_string());
-            }
+    /// Deletes the backup once the new binary has been confirmed healthy.
+    pub fn discard_backup(&self) -> Result<(), UpdaterError> {
+        let backup = update_plan::backup_path(&self.get_sidecar_path()?);
+        if backup.exists() {
+            fs::remove_file(&backup)?;
+        }
+        Ok(())
+    }
+
+    /// Installs `release` and returns its version. Events for the outcome are left to the
+    /// caller, which restarts and verifies the sidecar first.
+    pub fn perform_update(&self, release: &Release) -> Result<String, UpdaterError> {
+        // With a manifest configured, it alone decides what gets installed, including an
+        // older version it pins to roll back to.
+        let manifest_release = match Self::manifest_url() {
+            Some(url) => Some(self.get_manifest_release(&url)?),
+            None => None,
+        };
+        let release = manifest_release.as_ref().unwrap_or(release);
+
+        let target = self.get_target_asset_name();
+        let current_version = self
+            .get_current_version()
+            .unwrap_or_else(|_| "0.0.0".to_string());
+        let bin_path = self.get_sidecar_path()?;
+        let temp_dir = std::env::temp_dir().join(UPDATE_TEMP_PREFIX);
+
+        let plan = update_plan::plan(&PlanInput {
+            release,
+            target: &target,
+            current_version: &current_version,
+            bin_path: &bin_path,
+            bin_exists: bin_path.exists(),
+            temp_dir: &temp_dir,
+            allow_downgrade: manifest_release.is_some(),
+        })?;
+
+        match self.execute(&plan) {
+            Ok(()) => Ok(plan.version),
+            Err(e) if !bin_path.exists() => Err(self.fall_back_to_bundle(e)),
+            Err(e) => Err(e),
         }
+    }
 
-        Ok(version_str)
+    /// Reinstalls the bundled binary after a failed update left none in place, so the
+    /// app keeps working at the shipped version. Returns the error to report.
+    fn fall_back_to_bundle(&self, error: UpdaterError) -> UpdaterError {
+        eprintln!(
+            "[Updater] Update failed with no sidecar left ({}), reinstalling the bundled one",
+            error
+        );
+        let (restored, context) = match self.ensure_installed() {
+            Ok(_) => (true, "reinstalled the bundled sidecar instead".to_string()),
+            Err(e) => (
+                false,
+                format!("reinstalling the bundled sidecar also failed: {}", e),
+            ),
+        };
+        let _ = self.app_handle.emit(
+            "sidecar-update-fallback",
+            BundleFallback {
+                error: error.to_string(),
+                restored,
+            },
+        );
+        UpdaterError::WithContext {
+            error: Box::new(error),
+            context,
+        }
     }
 
-    fn get_target_asset_name(&self) -> String {
-        #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-        return "aarch64-apple-darwin".to_string();
+    /// Runs `plan` step by step. A failure after the current binary was backed up
+    /// restores the backup before the error is returned.
+    fn execute(&self, plan: &UpdatePlan) -> Result<(), UpdaterError> {
+        let mut downloaded: Option<Vec<u8>> = None;
+        let mut lock: Option<UpdateLock> = None;
 
-        #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
-        return "x86_64-apple-darwin".to_string();
+        for (index, step) in plan.steps.iter().enumerate() {
+            if let Err(e) = self.run_step(step, &mut downloaded, &mut lock) {
+                if let Some((backup, bin_path)) = plan.rollback_for(index) {
+                    if backup.exists() {
+                        eprintln!("[Updater] Update failed, restoring {:?}", backup);
+                        let _ = fs::remove_file(bin_path);
+                        if let Err(restore) = fs::rename(backup, bin_path) {
+                            eprintln!("[Updater] Failed to restore backup: {}", restore);
+                        }
+                    }
+                }
+                return Err(e);
+            }
+        }
 
-        #[cfg(target_os = "windows")]
-        return "x86_64-pc-windows-msvc".to_string();
+        println!("[Updater] Update to {} installed", plan.version);
+        Ok(())
+    }
 
-        #[cfg(target_os = "linux")]
-        return "x86_64-unknown-linux-gnu".to_string();
+    fn run_step(
+        &self,
+        step: &UpdateStep,
+        downloaded: &mut Option<Vec<u8>>,
+        lock: &mut Option<UpdateLock>,
+    ) -> Result<(), UpdaterError> {
+        match step {
+            UpdateStep::Download {
+                asset,
+                url,
+                checksum,
+            } => {
+                *downloaded = Some(self.download_verified(asset, url, checksum)?);
+            }
+            UpdateStep::Stage { archive_path } => {
+                let temp_dir = archive_path.parent().ok_or_else(|| {
+                    UpdaterError::Other("Staging path has no parent directory".to_string())
+                })?;
+                fs::create_dir_all(temp_dir)?;
+                *lock = Some(UpdateLock::acquire(temp_dir)?);
+                let bytes = downloaded.take().ok_or_else(|| {
+                    UpdaterError::Other("Nothing was downloaded to stage".to_string())
+                })?;
+                fs::write(archive_path, bytes)?;
+            }
+            UpdateStep::StopSidecar => {
+                // Through `shutdown`, so the crash monitor doesn't respawn the sidecar
+                // while its binary is being replaced. The kill below still catches a
+                // sidecar this session adopted rather than spawned.
+                SidecarManager::shutdown(&self.app_handle);
+                #[cfg(target_os = "windows")]
+                {
+                    let _ = process::command("taskkill")
+                        .args(["/F", "/IM", "opencode-cli.exe", "/T"])
+                        .output();
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                }
+                #[cfg(unix)]
+                {
+                    let _ = process::command("pkill")
+                        .args(["-f", "opencode-cli"])
+                        .output();
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                }
+            }
+            UpdateStep::BackupCurrent { from, to } => {
+                if to.exists() {
+                    let _ = fs::remove_file(to);
+                }
+                // On Windows, rename might fail if still locked.
+                if let Err(e) = fs::rename(from, to) {
+                    eprintln!("[Updater] Warning: Could not rename current binary: {}", e);
+                    // Try to remove it directly
+                    if let Err(e) = fs::remove_file(from) {
+                        return Err(UpdaterError::BinaryLocked(format!(
+                            "Could not remove current binary: {}",
+                            e
+                        )));
+                    }
+                }
+            }
+            UpdateStep::Install {
+                archive_path,
+                format,
+                bin_path,
+            } => match format {
+                ArchiveFormat::Zip => extract_zip(archive_path, bin_path)?,
+                ArchiveFormat::TarGz => extract_tar_gz(fs::File::open(archive_path)?, bin_path)?,
+                ArchiveFormat::Binary => {
+                    fs::copy(archive_path, bin_path)?;
+                }
+            },
+            UpdateStep::MakeExecutable { bin_path } => {
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    fs::set_permissions(bin_path, fs::Permissions::from_mode(0o755))?;
+                }
+                println!("[Updater] Update installed to {:?}", bin_path);
+            }
+        }
+        Ok(())
     }
 
-    pub fn perform_update(
+    /// Downloads `url`, fetching a companion checksum file concurrently, and fails if the
+    /// bytes don't match the expected digest.
+    fn download_verified(
         &self,
-        release: &Release,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let target = self.get_target_asset_name();
-        // Find asset that contains the target string
-        let asset = release
-            .assets
-            .iter()
-            .find(|a| a.name.contains(&target))
-            .ok_or_else(|| {
-                Box::<dyn std::error::Error + Send + Sync>::from(format!(
-                    "No matching asset found for target: {}",
-                    target
-                ))
-            })?;
+        asset: &str,
+        url: &str,
+        checksum: &ChecksumSource,
+    ) -> Result<Vec<u8>, UpdaterError> {
+        println!("[Updater] Downloading {}...", asset);
 
-        println!("[Updater] Downloading {}...", asset.name);
+        let (bytes, companion) = std::thread::scope(|scope| {
+            let companion = match checksum {
+                ChecksumSource::Companion { name, url } => {
+                    println!("[Updater] Downloading {}...", name);
+                    Some(scope.spawn(|| self.download(url)))
+                }
+                _ => None,
+            };
+            let mut last_emit = None;
+            let bytes = self.download_with_progress(url, |downloaded, total| {
+                self.emit_progress(&mut last_emit, downloaded, total)
+            });
+            let companion = companion.map(|handle| {
+                handle.join().unwrap_or_else(|_| {
+                    Err(UpdaterError::Other(
+                        "Checksum download thread panicked".to_string(),
+                    ))
+                })
+            });
+            (bytes, companion)
+        });
+        let bytes = bytes?;
 
-        let resp = self
-            .client
-            .get(&asset.browser_download_url)
-            .send()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-        if !resp.status().is_success() {
-            return Err(Box::<dyn std::error::Error + Send + Sync>::from(format!(
-                "Failed to download asset: {}",
-                resp.status()
-            )));
+        let expected = match (checksum, companion) {
+            (ChecksumSource::Companion { name, .. }, Some(content)) => {
+                let content = String::from_utf8(content?).map_err(|_| {
+                    UpdaterError::InvalidResponse(format!("{} is not a text file", name))
+                })?;
+                Some(parse_checksum_file(&content, asset).ok_or_else(|| {
+                    UpdaterError::InvalidResponse(format!(
+                        "{} does not contain a sha256 digest for {}",
+                        name, asset
+                    ))
+                })?)
+            }
+            (ChecksumSource::Pinned(digest), _) => Some(digest.clone()),
+            _ => None,
+        };
+
+        match &expected {
+            Some(expected) => {
+                verify_sha256(asset, &bytes, expected)?;
+                println!("[Updater] Verified sha256 of {}", asset);
+            }
+            None => eprintln!(
+                "[Updater] WARNING: no checksum published for {}, installing unverified",
+                asset
+            ),
+        }
+        Ok(bytes)
+    }
+}
+
+/// Copies the bundled binary to `install_path` and makes it executable.
+fn install_bundled(bundled_path: &Path, install_path: &Path) -> Result<(), UpdaterError> {
+    println!("[Updater] Found bundled binary at {:?}", bundled_path);
+    if let Some(parent) = install_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::copy(bundled_path, install_path)?;
+    println!("[Updater] Copied to {:?}", install_path);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(install_path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+/// Extracts the first entry containing "opencode" from the zip at `archive_path`.
+fn extract_zip(archive_path: &Path, bin_path: &Path) -> Result<(), UpdaterError> {
+    let file = fs::File::open(archive_path)?;
+    let mut archive = ZipArchive::new(file)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let name = file.name().to_string();
+        // We look for the executable inside the zip
+        // It might be nested or named differently, but usually contains "opencode"
+        if name.contains("opencode") && !name.ends_with("/") {
+            let mut out = fs::File::create(bin_path)?;
+            std::io::copy(&mut file, &mut out)?;
+            return Ok(());
         }
-        let bytes = resp
-            .bytes()
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    }
+    Err(UpdaterError::Extract(
+        "Could not extract executable from update archive".to_string(),
+    ))
+}
 
-        // Use a temp directory
-        let temp_dir = std::env::temp_dir().join("godoty-update");
-        if !temp_dir.exists() {
-            fs::create_dir_all(&temp_dir)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+/// Extracts the first file entry containing "opencode" from a gzipped tarball.
+fn extract_tar_gz(reader: impl Read, bin_path: &Path) -> Result<(), UpdaterError> {
+    let corrupt =
+        |e: std::io::Error| UpdaterError::Extract(format!("Corrupt update archive: {}", e));
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(reader));
+    for entry in archive.entries().map_err(corrupt)? {
+        let mut entry = entry.map_err(corrupt)?;
+        let name = entry.path().map_err(corrupt)?.to_string_lossy().to_string();
+        if name.contains("opencode") && entry.header().entry_type().is_file() {
+            let mut out = fs::File::create(bin_path)?;
+            std::io::copy(&mut entry, &mut out)?;
+            return Ok(());
         }
+    }
+    Err(UpdaterError::Extract(
+        "Could not extract executable from update archive".to_string(),
+    ))
+}
 
-        let archive_path = temp_dir.join(&asset.name);
-        fs::write(&archive_path, &bytes)
-            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+/// Runs `<bin_path> --version` and extracts the version; `0.0.0` if the binary fails.
+pub fn binary_version(bin_path: &Path) -> Result<String, UpdaterError> {
+    // Run `opencode-cli --version`
+    // Expected output: "opencode-cli 0.1.0" or just "0.1.0"
+    let output = process::command(bin_path).arg("--version").output()?;
 
-        // Prepare destination
-        let bin_path = self.get_sidecar_path()?;
+    if !output.status.success() {
+        return Ok("0.0.0".to_string());
+    }
 
-        // KILL existing process if running
-        #[cfg(target_os = "windows")]
-        {
-            let _ = std::process::Command::new("taskkill")
-                .args(["/F", "/IM", "opencode-cli.exe", "/T"])
-                .output();
-            std::thread::sleep(std::time::Duration::from_millis(500));
+    let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // Parse "opencode 0.1.2" -> "0.1.2"
+    if let Some(last) = version_str.split_whitespace().last() {
+        // simplistic check if it looks like a version
+        if last.contains('.') {
+            return Ok(last.to_string());
         }
-        #[cfg(unix)]
-        {
-            let _ = std::process::Command::new("pkill")
-                .args(["-f", "opencode-cli"])
-                .output();
-            std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+
+    Ok(version_str)
+}
+
+/// Makes the sidecar binary executable (`0o755`) if any execute bit is missing.
+pub fn fix_permissions(path: &Path) -> std::io::Result<PermissionFix> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let before = fs::metadata(path)?.permissions().mode() & 0o7777;
+        let after = if before & 0o111 == 0o111 {
+            before
+        } else {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+            println!(
+                "[Updater] Fixed sidecar permissions {:o} -> 755 on {:?}",
+                before, path
+            );
+            fs::metadata(path)?.permissions().mode() & 0o7777
+        };
+        Ok(PermissionFix {
+            path: path.to_string_lossy().to_string(),
+            before_mode: Some(before),
+            after_mode: Some(after),
+            changed: before != after,
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        fs::metadata(path)?;
+        Ok(PermissionFix {
+            path: path.to_string_lossy().to_string(),
+            before_mode: None,
+            after_mode: None,
+            changed: false,
+        })
+    }
+}
+
+/// Sends a GET and follows redirects by hand, attaching the `Authorization` header only
+/// while the request stays on the host it was issued for. GitHub asset downloads redirect
+/// to signed storage URLs on another host, which reject requests carrying credentials.
+fn get_following_redirects(
+    client: &Client,
+    url: &str,
+    token: Option<&str>,
+) -> Result<Response, UpdaterError> {
+    let origin =
+        Url::parse(url).map_err(|e| UpdaterError::Other(format!("Invalid URL {}: {}", url, e)))?;
+    let mut current = origin.clone();
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut request = client.get(current.clone());
+        if let Some(token) = token {
+            if is_same_host(&origin, &current) {
+                request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+            }
         }
 
-        // Move old binary to .old (backup/cleanup)
-        if bin_path.exists() {
-            let old_path = bin_path.with_extension("old");
-            if old_path.exists() {
-                let _ = fs::remove_file(&old_path);
+        let resp = request.send()?;
+        if !resp.status().is_redirection() {
+            return Ok(resp);
+        }
+
+        let location = resp
+            .headers()
+            .get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| {
+                UpdaterError::InvalidResponse(format!(
+                    "Redirect from {} has no Location header",
+                    current
+                ))
+            })?;
+        current = current.join(location).map_err(|e| {
+            UpdaterError::InvalidResponse(format!("Bad redirect from {}: {}", current, e))
+        })?;
+    }
+
+    Err(UpdaterError::InvalidResponse(format!(
+        "Too many redirects while fetching {}",
+        url
+    )))
+}
+
+/// Runs `send` up to `attempts` times, waiting `base_delay` and then twice as long before
+/// each further try. Transport errors and 5xx/429 responses are retried; any other
+/// response, including a 404, is returned as is.
+fn retry_get(
+    url: &str,
+    attempts: u32,
+    base_delay: Duration,
+    mut send: impl FnMut() -> Result<Response, UpdaterError>,
+) -> Result<Response, UpdaterError> {
+    let mut delay = base_delay;
+    let mut last_status = None;
+    let mut last_error = String::new();
+    for attempt in 1..=attempts {
+        let wait = match send() {
+            Ok(resp) if !is_retryable_status(resp.status()) => return Ok(resp),
+            Ok(resp) => {
+                last_status = Some(resp.status().as_u16());
+                last_error = format!("HTTP {}", resp.status());
+                retry_after(&resp).unwrap_or(delay)
             }
-            // On Windows, rename might fail if still locked.
-            if let Err(e) = fs::rename(&bin_path, &old_path) {
-                eprintln!("[Updater] Warning: Could not rename current binary: {}", e);
-                // Try to remove it directly
-                if let Err(e) = fs::remove_file(&bin_path) {
-                    return Err(Box::<dyn std::error::Error + Send + Sync>::from(format!(
-                        "Could not remove current binary: {}",
-                        e
-                    )));
-                }
+            // Anything else, such as a bad redirect, won't be fixed by asking again.
+            Err(UpdaterError::Network(e)) => {
+                last_status = None;
+                last_error = e;
+                delay
             }
+            Err(e) => return Err(e),
+        };
+        if attempt < attempts {
+            println!(
+                "[Updater] GET {} failed ({}), retrying in {:?} ({}/{})",
+                url, last_error, wait, attempt, attempts
+            );
+            std::thread::sleep(wait);
+            delay *= 2;
         }
+    }
+    let context = format!("GET {} failed after {} attempts", url, attempts);
+    Err(match last_status {
+        Some(status) => UpdaterError::Http { status, context },
+        None => UpdaterError::Network(format!("{}: {}", context, last_error)),
+    })
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// The `Retry-After` of a 429, when given in seconds.
+fn retry_after(resp: &Response) -> Option<Duration> {
+    if resp.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    let secs = resp
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+/// Highest non-draft release by semver precedence, falling back to publish date for tags
+/// that can't be compared. Pre-releases are skipped unless `allow_prerelease` is set.
+fn select_newest(releases: Vec<Release>, allow_prerelease: bool) -> Option<Release> {
+    releases
+        .into_iter()
+        .filter(|r| !r.draft && (allow_prerelease || !is_prerelease(r)))
+        .max_by(|a, b| {
+            version::compare(&a.tag_name, &b.tag_name)
+                .unwrap_or_else(|| a.published_at.cmp(&b.published_at))
+        })
+}
+
+/// Flagged as a pre-release on GitHub, or tagged like one.
+fn is_prerelease(release: &Release) -> bool {
+    release.prerelease || version::is_prerelease(&release.tag_name)
+}
+
+/// Reads the digest for `asset` from `sha256sum` output: either a single `<asset>.sha256`
+/// file or an aggregate `checksums.txt`. A line without a file name applies to any asset.
+fn parse_checksum_file(content: &str, asset: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let digest = parts.next()?;
+        let valid = digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit());
+        // `*` marks binary mode in sha256sum output.
+        let names_asset = parts
+            .next()
+            .map(|name| name.trim_start_matches('*').trim_start_matches("./"))
+            .is_none_or(|name| name == asset);
+        (valid && names_asset).then(|| digest.to_ascii_lowercase())
+    })
+}
 
-        // Extract
-        let mut extracted = false;
-        if asset.name.ends_with(".zip") {
-            let file = fs::File::open(&archive_path)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-            let mut archive = ZipArchive::new(file)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-
-            for i in 0..archive.len() {
-                let mut file = archive
-                    .by_index(i)
-                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                let name = file.name().to_string();
-                // We look for the executable inside the zip
-                // It might be nested or named differently, but usually contains "opencode"
-                if name.contains("opencode") && !name.ends_with("/") {
-                    let mut out = fs::File::create(&bin_path)
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                    std::io::copy(&mut file, &mut out)
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                    extracted = true;
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn verify_sha256(asset: &str, bytes: &[u8], expected: &str) -> Result<(), UpdaterError> {
+    let actual = sha256_hex(bytes);
+    if update_plan::checksum_matches(&actual, expected) {
+        Ok(())
+    } else {
+        Err(UpdaterError::ChecksumMismatch {
+            asset: asset.to_string(),
+            expected: expected.to_string(),
+            actual,
+        })
+    }
+}
+
+/// Release target triple for a `std::env::consts` OS and arch pair.
+fn target_triple(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("windows", "x86_64") => Some("x86_64-pc-windows-msvc"),
+        ("windows", "aarch64") => Some("aarch64-pc-windows-msvc"),
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-gnu"),
+        ("linux", "arm") => Some("armv7-unknown-linux-gnueabihf"),
+        _ => None,
+    }
+}
+
+fn is_github_url(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|u| {
+        u.host_str()
+            .is_some_and(|h| h == "github.com" || h.ends_with(".github.com"))
+    })
+}
+
+fn is_same_host(a: &Url, b: &Url) -> bool {
+    a.host_str() == b.host_str() && a.port_or_known_default() == b.port_or_known_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Accepts a single connection, replies with `response` and returns the raw request.
+    fn serve_once(listener: TcpListener, response: String) -> thread::JoinHandle<String> {
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
                     break;
                 }
+                request.extend_from_slice(&buf[..n]);
             }
-        } else {
-            // Treat as binary
-            fs::copy(&archive_path, &bin_path)
-                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-            extracted = true;
-        }
-
-        if extracted {
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                fs::set_permissions(&bin_path, fs::Permissions::from_mode(0o755))
-                    .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+            stream.write_all(response.as_bytes()).unwrap();
+            String::from_utf8_lossy(&request).to_lowercase()
+        })
+    }
+
+    /// Answers one connection per response, in order, and returns how many were served.
+    fn serve_each(listener: TcpListener, responses: Vec<String>) -> thread::JoinHandle<usize> {
+        thread::spawn(move || {
+            let count = responses.len();
+            for response in responses {
+                serve_once(listener.try_clone().unwrap(), response)
+                    .join()
+                    .unwrap();
             }
-            println!("[Updater] Update installed to {:?}", bin_path);
-        } else {
-            return Err(Box::<dyn std::error::Error + Send + Sync>::from(
-                "Could not extract executable from update archive",
-            ));
+            count
+        })
+    }
+
+    fn status_response(status: &str, extra_headers: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\n{}Content-Length: 0\r\nConnection: close\r\n\r\n",
+            status, extra_headers
+        )
+    }
+
+    #[test]
+    fn retries_server_errors_and_rate_limits() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/release", listener.local_addr().unwrap());
+        let server = serve_each(
+            listener,
+            vec![
+                status_response("503 Service Unavailable", ""),
+                status_response("429 Too Many Requests", "Retry-After: 0\r\n"),
+                status_response("200 OK", ""),
+            ],
+        );
+
+        let client = Client::builder().redirect(Policy::none()).build().unwrap();
+        let resp = retry_get(&url, 3, Duration::ZERO, || {
+            get_following_redirects(&client, &url, None)
+        })
+        .unwrap();
+
+        assert!(resp.status().is_success());
+        assert_eq!(server.join().unwrap(), 3);
+    }
+
+    #[test]
+    fn does_not_retry_not_found() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/release", listener.local_addr().unwrap());
+        let server = serve_once(listener, status_response("404 Not Found", ""));
+
+        let client = Client::builder().redirect(Policy::none()).build().unwrap();
+        let mut sent = 0;
+        let resp = retry_get(&url, 3, Duration::ZERO, || {
+            sent += 1;
+            get_following_redirects(&client, &url, None)
+        })
+        .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert_eq!(sent, 1);
+    }
+
+    #[test]
+    fn reports_attempts_when_retries_run_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/release", listener.local_addr().unwrap());
+        let server = serve_each(
+            listener,
+            vec![status_response("500 Internal Server Error", ""); 3],
+        );
+
+        let client = Client::builder().redirect(Policy::none()).build().unwrap();
+        let err = retry_get(&url, 3, Duration::ZERO, || {
+            get_following_redirects(&client, &url, None)
+        })
+        .unwrap_err();
+        server.join().unwrap();
+
+        assert_eq!(err.kind(), "http");
+        assert_eq!(err.status(), Some(500));
+        assert!(
+            err.to_string().contains("failed after 3 attempts"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn cross_host_redirect_drops_authorization() {
+        let api_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_port = api_listener.local_addr().unwrap().port();
+        let asset_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let asset_port = asset_listener.local_addr().unwrap().port();
+
+        // The asset server is addressed as `localhost`, a different host from the API's.
+        let redirect = format!(
+            "HTTP/1.1 302 Found\r\nLocation: http://localhost:{}/asset\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            asset_port
+        );
+        let ok = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok".to_string();
+        let api = serve_once(api_listener, redirect);
+        let asset = serve_once(asset_listener, ok);
+
+        let client = Client::builder().redirect(Policy::none()).build().unwrap();
+        let url = format!("http://127.0.0.1:{}/release", api_port);
+        let resp = get_following_redirects(&client, &url, Some("secret")).unwrap();
+
+        assert!(resp.status().is_success());
+        assert_eq!(resp.text().unwrap(), "ok");
+        assert!(api.join().unwrap().contains("authorization: bearer secret"));
+        assert!(!asset.join().unwrap().contains("authorization"));
+    }
+
+    #[test]
+    fn same_host_requires_matching_port_and_scheme() {
+        let api = Url::parse("https://api.github.com/repos").unwrap();
+        assert!(is_same_host(
+            &api,
+            &Url::parse("https://api.github.com:443/x").unwrap()
+        ));
+        assert!(!is_same_host(
+            &api,
+            &Url::parse("https://objects.githubusercontent.com/x").unwrap()
+        ));
+        assert!(!is_same_host(
+            &api,
+            &Url::parse("http://api.github.com/x").unwrap()
+        ));
+    }
+
+    fn release(tag: &str, published_at: &str, prerelease: bool, draft: bool) -> Release {
+        Release {
+            tag_name: tag.to_string(),
+            assets: Vec::new(),
+            body: None,
+            published_at: Some(published_at.to_string()),
+            prerelease,
+            draft,
         }
+    }
 
-        Ok(())
+    #[test]
+    fn release_list_fallback_prefers_newest_stable() {
+        let releases = vec![
+            release("v1.3.0-beta.1", "2025-03-01T00:00:00Z", true, false),
+            release("v1.2.0", "2025-02-01T00:00:00Z", false, false),
+            release("v1.1.0", "2025-01-01T00:00:00Z", false, false),
+            release("v1.4.0", "2025-04-01T00:00:00Z", false, true),
+        ];
+        assert_eq!(select_newest(releases, false).unwrap().tag_name, "v1.2.0");
+
+        let only_pre = vec![
+            release("v2.0.0-rc.1", "2025-05-01T00:00:00Z", true, false),
+            release("v2.0.0-rc.2", "2025-06-01T00:00:00Z", true, false),
+        ];
+        assert!(select_newest(only_pre.clone(), false).is_none());
+        assert_eq!(
+            select_newest(only_pre, true).unwrap().tag_name,
+            "v2.0.0-rc.2"
+        );
+        assert!(select_newest(Vec::new(), true).is_none());
+    }
+
+    #[test]
+    fn prerelease_opt_in_uses_semver_precedence() {
+        // Publish order deliberately disagrees with version order.
+        let releases = vec![
+            release("v1.2.0", "2025-03-01T00:00:00Z", false, false),
+            release("v1.3.0-rc.1", "2025-02-01T00:00:00Z", true, false),
+            release("v1.3.0-beta.2", "2025-04-01T00:00:00Z", true, false),
+            // Tagged as a pre-release but not flagged as one.
+            release("v1.3.0-rc.2", "2025-01-01T00:00:00Z", false, false),
+        ];
+        assert_eq!(
+            select_newest(releases.clone(), true).unwrap().tag_name,
+            "v1.3.0-rc.2"
+        );
+        assert_eq!(select_newest(releases, false).unwrap().tag_name, "v1.2.0");
+
+        let with_final = vec![
+            release("v1.3.0-rc.2", "2025-05-01T00:00:00Z", true, false),
+            release("v1.3.0", "2025-04-01T00:00:00Z", false, false),
+        ];
+        assert_eq!(select_newest(with_final, true).unwrap().tag_name, "v1.3.0");
+    }
+
+    #[test]
+    fn version_manifest_becomes_a_checksummed_release() {
+        let manifest: VersionManifest = serde_json::from_str(
+            r#"{
+                "version": "1.2.3",
+                "assets": [{
+                    "name": "opencode-x86_64-unknown-linux-gnu.zip",
+                    "url": "https://mirror.example.com/opencode.zip",
+                    "sha256": "ABC123"
+                }]
+            }"#,
+        )
+        .unwrap();
+        let release = Release::from(manifest);
+
+        assert_eq!(release.tag_name, "1.2.3");
+        let asset = update_plan::select_asset(&release, "x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(
+            asset.browser_download_url,
+            "https://mirror.example.com/opencode.zip"
+        );
+        assert_eq!(asset.sha256.as_deref(), Some("ABC123"));
+    }
+
+    #[test]
+    fn github_token_is_scoped_to_github_hosts() {
+        assert!(is_github_url("https://api.github.com/repos/a/b"));
+        assert!(is_github_url("https://github.com/a/b/releases"));
+        assert!(!is_github_url("https://mirror.example.com/manifest.json"));
+        assert!(!is_github_url("https://github.com.evil.example/x"));
+    }
+
+    #[test]
+    fn verifies_sha256_against_sibling_and_aggregate_files() {
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert_eq!(sha256_hex(b"abc"), abc);
+        assert!(verify_sha256("opencode.zip", b"abc", abc).is_ok());
+        assert!(verify_sha256("opencode.zip", b"abc", &abc.to_uppercase()).is_ok());
+        let err = verify_sha256("opencode.zip", b"abd", abc).unwrap_err();
+        assert_eq!(err.kind(), "checksum_mismatch");
+        assert!(err
+            .to_string()
+            .contains("Checksum mismatch for opencode.zip"));
+
+        let other = "0".repeat(64);
+        let aggregate = format!("{}  opencode-a.zip\n{} *opencode.zip\n", other, abc);
+        assert_eq!(
+            parse_checksum_file(&aggregate, "opencode.zip").as_deref(),
+            Some(abc)
+        );
+        assert_eq!(parse_checksum_file(&aggregate, "missing.zip"), None);
+        assert_eq!(
+            parse_checksum_file(&format!("{}\n", abc), "opencode.zip").as_deref(),
+            Some(abc)
+        );
+    }
+
+    #[test]
+    fn extracts_opencode_entry_from_tar_gz() {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let mut append = |path: &str, data: &[u8], kind: tar::EntryType| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(kind);
+            header.set_size(data.len() as u64);
+            header.set_mode(0o755);
+            builder.append_data(&mut header, path, data).unwrap();
+        };
+        append("README.md", b"readme", tar::EntryType::Regular);
+        append("opencode-linux-x64/", b"", tar::EntryType::Directory);
+        append(
+            "opencode-linux-x64/opencode",
+            b"binary",
+            tar::EntryType::Regular,
+        );
+        let archive = builder.into_inner().unwrap().finish().unwrap();
+
+        let dir = std::env::temp_dir().join(format!("godoty-tgz-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let bin_path = dir.join("opencode-cli");
+        extract_tar_gz(archive.as_slice(), &bin_path).unwrap();
+        let extracted = fs::read(&bin_path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(extracted, b"binary");
+    }
+
+    #[test]
+    fn release_urls_follow_a_custom_repo() {
+        let default = ReleaseSource::parse(None, None);
+        assert_eq!(
+            default.latest_release_url(),
+            "https://api.github.com/repos/anomalyco/opencode/releases/latest"
+        );
+
+        let fork = ReleaseSource::parse(Some(" me/opencode-fork "), None);
+        assert_eq!(
+            fork.releases_url(),
+            "https://api.github.com/repos/me/opencode-fork/releases"
+        );
+
+        let enterprise = ReleaseSource::parse(
+            Some("team/opencode"),
+            Some("https://ghe.example.com/api/v3/"),
+        );
+        assert_eq!(
+            enterprise.latest_release_url(),
+            "https://ghe.example.com/api/v3/repos/team/opencode/releases/latest"
+        );
+        assert!(enterprise.is_api_host("https://ghe.example.com/api/v3/repos/team/opencode"));
+        assert!(!enterprise.is_api_host("https://mirror.example.com/opencode.zip"));
+    }
+
+    #[test]
+    fn malformed_repo_falls_back_to_default() {
+        for repo in ["opencode", "a/b/c", "/name", "owner/", "own er/name"] {
+            assert_eq!(
+                ReleaseSource::parse(Some(repo), None),
+                ReleaseSource::parse(None, None),
+                "{}",
+                repo
+            );
+        }
+        assert_eq!(
+            ReleaseSource::parse(None, Some("not a url")).api_base,
+            GITHUB_API_BASE
+        );
+    }
+
+    #[test]
+    fn maps_os_and_arch_to_release_targets() {
+        let cases = [
+            ("macos", "aarch64", Some("aarch64-apple-darwin")),
+            ("macos", "x86_64", Some("x86_64-apple-darwin")),
+            ("windows", "x86_64", Some("x86_64-pc-windows-msvc")),
+            ("windows", "aarch64", Some("aarch64-pc-windows-msvc")),
+            ("linux", "x86_64", Some("x86_64-unknown-linux-gnu")),
+            ("linux", "aarch64", Some("aarch64-unknown-linux-gnu")),
+            ("linux", "arm", Some("armv7-unknown-linux-gnueabihf")),
+            ("linux", "riscv64", None),
+            ("freebsd", "x86_64", None),
+        ];
+        for (os, arch, expected) in cases {
+            assert_eq!(target_triple(os, arch), expected, "{} {}", os, arch);
+        }
+    }
+
+    #[test]
+    fn only_one_concurrent_update_is_admitted() {
+        let guard = Arc::new(UpdateGuard::default());
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        let attempts: Vec<_> = (0..2)
+            .map(|_| {
+                let guard = guard.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    let admitted = guard.try_begin();
+                    // Hold the guard long enough for the other call to overlap.
+                    thread::sleep(std::time::Duration::from_millis(50));
+                    admitted.map(|_| ())
+                })
+            })
+            .collect();
+        let results: Vec<_> = attempts.into_iter().map(|t| t.join().unwrap()).collect();
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert!(results
+            .iter()
+            .any(|r| matches!(r, Err(UpdaterError::InProgress))));
+        // Released once the admitted update finished.
+        assert!(guard.try_begin().is_ok());
+    }
+
+    #[test]
+    fn errors_serialize_with_the_kind_of_their_cause() {
+        let error = UpdaterError::WithContext {
+            error: Box::new(UpdaterError::Http {
+                status: 404,
+                context: "Failed to fetch release".to_string(),
+            }),
+            context: "rolled back to the previous sidecar".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "http",
+                "message": "Failed to fetch release: HTTP 404; rolled back to the previous sidecar",
+                "status": 404,
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(UpdaterError::InProgress).unwrap()["status"],
+            serde_json::Value::Null
+        );
     }
 }
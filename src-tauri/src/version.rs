@@ -0,0 +1,161 @@
+use regex::Regex;
+use semver::{BuildMetadata, Prerelease, Version};
+use std::cmp::Ordering;
+use std::sync::LazyLock;
+
+/// First `major.minor[.patch][-pre][+build]` run inside a tag such as `sidecar-v1.2.3`.
+static VERSION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(\d+)\.(\d+)(?:\.(\d+))?(?:-([0-9A-Za-z-]+(?:\.[0-9A-Za-z-]+)*))?(?:\+[0-9A-Za-z.-]+)?",
+    )
+    .unwrap()
+});
+
+/// Version reported by `Updater::get_current_version` when no sidecar is installed.
+const NOT_INSTALLED: &str = "0.0.0";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedVersion {
+    Semver(Version),
+    /// Date-based version such as `2024.11.1`, compared component by component.
+    Calver(Vec<u64>),
+    Unparseable,
+}
+
+/// Whether two versions could be ordered, so the UI never offers a bogus update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionStatus {
+    Comparable,
+    Unparseable,
+}
+
+pub fn parse(tag: &str) -> ParsedVersion {
+    let Some(caps) = VERSION_RE.captures(tag) else {
+        return ParsedVersion::Unparseable;
+    };
+    let number = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<u64>().ok());
+
+    let (Some(major), Some(minor)) = (number(1), number(2)) else {
+        return ParsedVersion::Unparseable;
+    };
+    let patch = number(3);
+
+    // A four-digit leading component is a year, not a semver major.
+    if caps[1].len() == 4 && (1990..=2999).contains(&major) {
+        let mut parts = vec![major, minor];
+        parts.extend(patch);
+        return ParsedVersion::Calver(parts);
+    }
+
+    let pre = match caps.get(4) {
+        Some(m) => match Prerelease::new(m.as_str()) {
+            Ok(pre) => pre,
+            Err(_) => return ParsedVersion::Unparseable,
+        },
+        None => Prerelease::EMPTY,
+    };
+    ParsedVersion::Semver(Version {
+        major,
+        minor,
+        patch: patch.unwrap_or(0),
+        pre,
+        build: BuildMetadata::EMPTY,
+    })
+}
+
+/// Orders `latest` against `current`, or `None` when either is unparseable or the two
+/// use different schemes. A missing sidecar (`0.0.0`) is older than any parsed version.
+pub fn compare(latest: &str, current: &str) -> Option<Ordering> {
+    match (parse(latest), parse(current)) {
+        (ParsedVersion::Semver(a), ParsedVersion::Semver(b)) => Some(a.cmp(&b)),
+        (ParsedVersion::Calver(a), ParsedVersion::Calver(b)) => Some(compare_calver(&a, &b)),
+        (ParsedVersion::Unparseable, _) => None,
+        (_, _) if current.trim_start_matches('v') == NOT_INSTALLED => Some(Ordering::Greater),
+        _ => None,
+    }
+}
+
+/// Whether `tag` is a semver pre-release such as `v1.3.0-rc.1`.
+pub fn is_prerelease(tag: &str) -> bool {
+    matches!(parse(tag), ParsedVersion::Semver(v) if !v.pre.is_empty())
+}
+
+fn compare_calver(a: &[u64], b: &[u64]) -> Ordering {
+    let len = a.len().max(b.len());
+    let part = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    (0..len)
+        .map(|i| part(a, i).cmp(&part(b, i)))
+        .find(|o| o.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn semver(s: &str) -> ParsedVersion {
+        ParsedVersion::Semver(Version::parse(s).unwrap())
+    }
+
+    #[test]
+    fn extracts_semver_from_prefixed_tags() {
+        assert_eq!(parse("v1.2.3"), semver("1.2.3"));
+        assert_eq!(parse("sidecar-v1.2.3"), semver("1.2.3"));
+        assert_eq!(parse("opencode 0.14.1"), semver("0.14.1"));
+        assert_eq!(parse("v1.2.3-beta.2+build.5"), semver("1.2.3-beta.2"));
+        assert_eq!(parse("v1.4"), semver("1.4.0"));
+    }
+
+    #[test]
+    fn recognises_calver_tags() {
+        assert_eq!(parse("2024.11.1"), ParsedVersion::Calver(vec![2024, 11, 1]));
+        assert_eq!(
+            parse("release-2025.01.07"),
+            ParsedVersion::Calver(vec![2025, 1, 7])
+        );
+        assert_eq!(parse("2024.11"), ParsedVersion::Calver(vec![2024, 11]));
+    }
+
+    #[test]
+    fn flags_unparseable_tags() {
+        assert_eq!(parse("nightly"), ParsedVersion::Unparseable);
+        assert_eq!(parse(""), ParsedVersion::Unparseable);
+        assert_eq!(parse("v1.2.3-01"), ParsedVersion::Unparseable);
+    }
+
+    #[test]
+    fn compares_within_a_scheme() {
+        assert_eq!(compare("sidecar-v1.10.0", "1.9.3"), Some(Ordering::Greater));
+        assert_eq!(compare("v1.2.3", "1.2.3"), Some(Ordering::Equal));
+        assert_eq!(compare("2024.11.1", "2024.9.30"), Some(Ordering::Greater));
+        assert_eq!(compare("2024.11", "2024.11.2"), Some(Ordering::Less));
+    }
+
+    #[test]
+    fn orders_prereleases_below_their_release() {
+        assert_eq!(compare("0.2.0", "0.2.0-rc.1"), Some(Ordering::Greater));
+        assert_eq!(compare("0.2.0-rc.1", "0.2.0"), Some(Ordering::Less));
+        assert_eq!(
+            compare("0.2.0-rc.1", "0.2.0-beta.3"),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(compare("0.2.1-alpha.1", "0.2.0"), Some(Ordering::Greater));
+        assert!(is_prerelease("v0.2.0-rc.1"));
+        assert!(!is_prerelease("v0.2.0"));
+    }
+
+    #[test]
+    fn refuses_to_compare_across_schemes_or_garbage() {
+        assert_eq!(compare("2024.11.1", "1.2.3"), None);
+        assert_eq!(compare("nightly", "1.2.3"), None);
+        assert_eq!(compare("1.2.3", "unknown"), None);
+    }
+
+    #[test]
+    fn anything_parsed_beats_a_missing_install() {
+        assert_eq!(compare("2024.11.1", "0.0.0"), Some(Ordering::Greater));
+        assert_eq!(compare("v1.0.0", "0.0.0"), Some(Ordering::Greater));
+        assert_eq!(compare("nightly", "0.0.0"), None);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::config::get_config_dir;
+use crate::settings::{self, SETTINGS_FILE};
+use crate::sidecar::SidecarManager;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+pub const OPENCODE_CONFIG_FILE: &str = "opencode.json";
+/// Changes are applied once the files have been quiet this long, so an editor's
+/// write-rename-chmod sequence causes a single reload.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Holds the active watcher; dropping it stops the watch thread.
+#[derive(Default)]
+pub struct ConfigWatcherState {
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    /// Hash of each watched file as Godoty last wrote or applied it, so events for content
+    /// already in effect (including Godoty's own writes) are skipped.
+    applied: Mutex<HashMap<String, u64>>,
+}
+
+/// Payload of the `config-changed` event.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ConfigChange {
+    pub file: String,
+    /// `settings_reloaded`, `sidecar_reloaded` or `invalid`.
+    pub action: &'static str,
+    pub error: Option<String>,
+}
+
+/// Starts or stops watching `opencode.json` and `godoty.toml` for external edits.
+pub fn set_enabled<R: Runtime>(app: &AppHandle<R>, enabled: bool) -> Result<(), String> {
+    let state = app
+        .try_state::<ConfigWatcherState>()
+        .ok_or("Config watcher state is not managed")?;
+    let mut current = state.watcher.lock().unwrap();
+
+    if !enabled {
+        if current.take().is_some() {
+            println!("[Watcher] Stopped watching config files");
+        }
+        return Ok(());
+    }
+    if current.is_some() {
+        return Ok(());
+    }
+
+    let config_dir = get_config_dir(app).map_err(|e| e.to_string())?;
+    for file in [OPENCODE_CONFIG_FILE, SETTINGS_FILE] {
+        if let Ok(content) = fs::read(config_dir.join(file)) {
+            is_new_content(&state.applied, file, &content);
+        }
+    }
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+    // The directory is watched because editors often replace files instead of writing them.
+    watcher
+        .watch(&config_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| e.to_string())?;
+
+    let app = app.clone();
+    thread::spawn(move || {
+        // Ends when the watcher is dropped and the channel disconnects.
+        while let Ok(first) = rx.recv() {
+            let mut changed = BTreeSet::new();
+            collect(first, &mut changed);
+            loop {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(event) => collect(event, &mut changed),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            for file in changed {
+                handle_change(&app, &file);
+            }
+        }
+    });
+
+    println!("[Watcher] Watching config files in {:?}", config_dir);
+    *current = Some(watcher);
+    Ok(())
+}
+
+fn collect(event: notify::Result<notify::Event>, changed: &mut BTreeSet<String>) {
+    let Ok(event) = event else { return };
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+    changed.extend(
+        event
+            .paths
+            .iter()
+            .filter_map(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .filter(|n| n == OPENCODE_CONFIG_FILE || n == SETTINGS_FILE),
+    );
+}
+
+/// Records content Godoty itself wrote to a watched file, so the watcher doesn't apply it
+/// a second time.
+pub fn record_write<R: Runtime>(app: &AppHandle<R>, file: &str, content: &[u8]) {
+    if let Some(state) = app.try_state::<ConfigWatcherState>() {
+        is_new_content(&state.applied, file, content);
+    }
+}
+
+/// Whether `content` differs from what was last recorded for `file`, recording it if so.
+fn is_new_content(applied: &Mutex<HashMap<String, u64>>, file: &str, content: &[u8]) -> bool {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    let hash = hasher.finish();
+    applied.lock().unwrap().insert(file.to_string(), hash) != Some(hash)
+}
+
+fn handle_change<R: Runtime>(app: &AppHandle<R>, file: &str) {
+    let content = get_config_dir(app)
+        .ok()
+        .and_then(|dir| fs::read(dir.join(file)).ok())
+        .unwrap_or_default();
+    if let Some(state) = app.try_state::<ConfigWatcherState>() {
+        if !is_new_content(&state.applied, file, &content) {
+            return;
+        }
+    }
+    println!("[Watcher] {} changed on disk", file);
+    let change = if file == SETTINGS_FILE {
+        settings::reload(app);
+        ConfigChange {
+            file: file.to_string(),
+            action: "settings_reloaded",
+            error: None,
+        }
+    } else {
+        // Applied as edited; re-templating from the bundle would discard the user's edit.
+        match validate_opencode_config(app) {
+            Ok(()) => {
+                SidecarManager::apply_config(app);
+                ConfigChange {
+                    file: file.to_string(),
+                    action: "sidecar_reloaded",
+                    error: None,
+                }
+            }
+            Err(e) => {
+                eprintln!("[Watcher] Not reloading invalid {}: {}", file, e);
+                ConfigChange {
+                    file: file.to_string(),
+                    action: "invalid",
+                    error: Some(e),
+                }
+            }
+        }
+    };
+    let _ = app.emit("config-changed", change);
+}
+
+fn validate_opencode_config<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
+    let path = get_config_dir(app)
+        .map_err(|e| e.to_string())?
+        .join(OPENCODE_CONFIG_FILE);
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str::<serde_json::Value>(&content)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_content_is_not_new() {
+        let applied = Mutex::new(HashMap::new());
+        assert!(is_new_content(&applied, SETTINGS_FILE, b"a = 1"));
+        assert!(!is_new_content(&applied, SETTINGS_FILE, b"a = 1"));
+        assert!(is_new_content(&applied, OPENCODE_CONFIG_FILE, b"a = 1"));
+        assert!(is_new_content(&applied, SETTINGS_FILE, b"a = 2"));
+    }
+}
@@ -1,4 +1,6 @@
-import { Component } from "solid-js";
+import { Component, createSignal, onCleanup, onMount, Show } from "solid-js";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import "@opencode-ai/ui/styles/index.css";
 import { AppBaseProviders, AppInterface, PlatformProvider, type Platform } from "@opencode-ai/app";
 import "./App.css";
@@ -19,6 +21,25 @@ const platform: Platform = {
 };
 
 const App: Component = () => {
+  // The sidecar falls back to another port when GODOTY_PORT is taken, and may move again
+  // when it restarts, so the server URL comes from the backend rather than a fixed port.
+  // @ts-ignore
+  const inTauri = !!window.__TAURI_INTERNALS__;
+  const [port, setPort] = createSignal<number>();
+  let unlisten: (() => void) | undefined;
+  onCleanup(() => unlisten?.());
+
+  onMount(async () => {
+    if (!inTauri) return;
+    unlisten = await listen<number>("sidecar-port-changed", (e) => setPort(e.payload));
+    try {
+      setPort(await invoke<number>("get_sidecar_port"));
+    } catch (e) {
+      console.error("Failed to get sidecar port", e);
+      setPort(4096);
+    }
+  });
+
   return (
     <div class="h-screen w-screen bg-gray-50 text-gray-900 dark:bg-gray-900 dark:text-white flex flex-col">
       <UpdateBanner />
@@ -26,7 +47,9 @@ const App: Component = () => {
       <div class="flex-1 overflow-hidden flex flex-col">
         <PlatformProvider value={platform}>
           <AppBaseProviders>
-            <AppInterface />
+            <Show when={!inTauri || port()}>
+              <AppInterface defaultUrl={port() ? `http://localhost:${port()}` : undefined} />
+            </Show>
           </AppBaseProviders>
         </PlatformProvider>
       </div>
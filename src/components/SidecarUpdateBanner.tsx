@@ -1,6 +1,27 @@
 import { Component, createSignal, onMount, Show } from "solid-js";
 import { invoke } from "@tauri-apps/api/core";
 
+/** Error returned by the updater commands; `kind` is stable, `message` is for display. */
+type UpdaterError = { kind: string; message: string; status: number | null };
+
+const describeUpdaterError = (e: unknown): string => {
+  const err = e as Partial<UpdaterError> | null;
+  switch (err?.kind) {
+    case "network":
+      return "could not reach the update server";
+    case "no_asset":
+      return "no build is published for this platform";
+    case "checksum_mismatch":
+      return "the download was corrupted, please try again";
+    case "binary_locked":
+      return "the sidecar is still running, close it and try again";
+    case "in_progress":
+      return "an update is already running";
+    default:
+      return err?.message ?? String(e);
+  }
+};
+
 const SidecarUpdateBanner: Component = () => {
   const [updateAvailable, setUpdateAvailable] = createSignal<boolean>(false);
   const [version, setVersion] = createSignal<string>("");
@@ -40,7 +61,7 @@ const SidecarUpdateBanner: Component = () => {
       alert("Sidecar updated successfully!");
     } catch (e) {
       console.error("Failed to install sidecar update", e);
-      setError("Installation failed: " + String(e));
+      setError("Installation failed: " + describeUpdaterError(e));
     } finally {
       setUpdating(false);
     }